@@ -1,4 +1,16 @@
 fn main() {
     cc::Build::new().file("src/wrappers.c").compile("wrappers");
     println!("cargo:rerun-if-changed=src");
+
+    // Recorded in the run metadata so results identify the tests they ran.
+    let revision = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=FSTESTING_REVISION={revision}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
 }
@@ -1,47 +1,251 @@
-// Compare the behavior of two filesystems by running a random set of "commands"
-// against a read/write file on both filesystems and comparing the results.
+// Compare the behavior of a filesystem against an in-memory model, or against
+// a reference directory on another filesystem, by running a random set of
+// "commands" against both and comparing the results.
+//
+// The minimized sequence of a failing run is saved to the regressions
+// directory so `check-regressions` can replay it. Every run prints its seed;
+// passing the same seed again generates the same commands and data.
+//
+// How often each command is generated can be changed with `--weight` or a
+// YAML file of weights given to `--weights`, and the mix of commands that
+// were run is printed at the end.
 
-use std::io::{Error, ErrorKind, Result};
+use std::collections::BTreeMap;
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
-use quickcheck::quickcheck;
+use quickcheck::{Gen, QuickCheck};
 
-use fstesting::commands::{Command, CommandsTest, MAX_FILE_SIZE};
+use fstesting::commands::{
+    COMMAND_NAMES, COMMAND_WEIGHTS, Command, Commands, CommandsTest,
+    EQUIVALENT_ERRNOS, MAX_FILE_SIZE, Regression, command_weights,
+    parse_errnos, save_regression,
+};
 
-fn run_test(commands: Vec<Command>) -> Result<()> {
-    let args = std::env::args().collect::<Vec<_>>();
-    assert_eq!(args.len(), 4);
+const DEFAULT_REGRESSIONS: &str = "regressions";
+const DEFAULT_TESTS: u64 = 100;
+const DEFAULT_MAX_COMMANDS: usize = 100;
 
-    let ret =
-        CommandsTest::new(args[1].clone(), args[2].clone())?.run(commands);
+struct Args {
+    dir: String,
+    reference: Option<String>,
+    max_file_size: usize,
+    regressions: PathBuf,
+    seed: u64,
+    tests: u64,
+    max_commands: usize,
+    equivalent: Vec<Vec<i32>>,
+    weights: Vec<u32>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = std::env::args();
+        let prog = args.next().unwrap_or_default();
+        let usage = || {
+            Error::other(format!(
+                "usage: {prog} [--reference DIR] [--seed N] [--tests N] \
+                 [--max-commands N] [--equivalent ERRNO,ERRNO...] \
+                 [--weights FILE] [--weight COMMAND=N...] \
+                 DIR MAX_FILE_SIZE_MB [REGRESSIONS_DIR]"
+            ))
+        };
+
+        let mut positional = Vec::new();
+        let mut reference = None;
+        let mut seed = None;
+        let mut tests = DEFAULT_TESTS;
+        let mut max_commands = DEFAULT_MAX_COMMANDS;
+        let mut equivalent = Vec::new();
+        let mut weights = BTreeMap::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--reference" => {
+                    reference = Some(args.next().ok_or_else(usage)?);
+                }
+                "--equivalent" => {
+                    let value = args.next().ok_or_else(usage)?;
+                    equivalent.push(parse_errnos(&value)?);
+                }
+                "--weights" => {
+                    let path = args.next().ok_or_else(usage)?;
+                    weights.extend(load_weights(Path::new(&path))?);
+                }
+                "--weight" => {
+                    let value = args.next().ok_or_else(usage)?;
+                    let (name, weight) = parse_weight(&value)?;
+                    weights.insert(name, weight);
+                }
+                "--seed" | "--tests" | "--max-commands" => {
+                    let value = args.next().ok_or_else(usage)?;
+                    let value = value.parse::<u64>().map_err(|_| {
+                        Error::other(format!(
+                            "Invalid value for {arg}: {value}"
+                        ))
+                    })?;
+                    match arg.as_str() {
+                        "--seed" => seed = Some(value),
+                        "--tests" => tests = value,
+                        _ => max_commands = value as usize,
+                    }
+                }
+                _ if arg.starts_with("--") => return Err(usage()),
+                _ => positional.push(arg),
+            }
+        }
+
+        if positional.len() != 2 && positional.len() != 3 {
+            return Err(usage());
+        }
+
+        let max_file_size = positional[1]
+            .parse::<usize>()
+            .expect("Invalid maximum file size");
+        let regressions = PathBuf::from(
+            positional
+                .get(2)
+                .map_or(DEFAULT_REGRESSIONS, |d| d.as_str()),
+        );
+
+        Ok(Self {
+            dir: positional[0].clone(),
+            reference,
+            max_file_size,
+            regressions,
+            seed: seed.unwrap_or_else(rand::random),
+            tests,
+            max_commands,
+            equivalent,
+            weights: command_weights(&weights)?,
+        })
+    }
+}
+
+// Parse a `COMMAND=WEIGHT` setting.
+fn parse_weight(value: &str) -> Result<(String, u32)> {
+    let invalid = || Error::other(format!("Invalid weight: {value}"));
+    let (name, weight) = value.split_once('=').ok_or_else(invalid)?;
+    let weight = weight.trim().parse::<u32>().map_err(|_| invalid())?;
+    Ok((name.trim().to_owned(), weight))
+}
+
+// Load a YAML map of command names to weights.
+fn load_weights(path: &Path) -> Result<BTreeMap<String, u32>> {
+    let contents = std::fs::read_to_string(path)?;
+    serde_yaml::from_str(&contents).map_err(|e| {
+        Error::other(format!("Invalid weights {}: {e}", path.display()))
+    })
+}
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+// quickcheck shrinks a failure by re-running smaller candidates, recursing on
+// each one that still fails, so the last failing sequence is the minimal one.
+static LAST_FAILURE: Mutex<Option<Vec<Command>>> = Mutex::new(None);
+
+// How many times each command was run, by index in `COMMAND_NAMES`. Runs made
+// while shrinking a failure aren't counted.
+static MIX: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn count_mix(commands: &[Command]) {
+    if LAST_FAILURE.lock().unwrap().is_some() {
+        return;
+    }
+
+    let mut mix = MIX.lock().unwrap();
+    mix.resize(COMMAND_NAMES.len(), 0);
+    for cmd in commands.iter() {
+        let idx = COMMAND_NAMES.iter().position(|n| *n == cmd.name()).unwrap();
+        mix[idx] += 1;
+    }
+}
+
+fn print_mix() {
+    let mix = MIX.lock().unwrap();
+    let total = mix.iter().sum::<usize>().max(1);
+    let weights = &ARGS.get().unwrap().weights;
+
+    eprintln!("\nOperation mix:");
+    for (idx, name) in COMMAND_NAMES.iter().enumerate() {
+        let count = mix.get(idx).copied().unwrap_or(0);
+        eprintln!(
+            "  {name:<10} {count:>8} {:>6.2}%  (weight {})",
+            count as f64 * 100.0 / total as f64,
+            weights[idx]
+        );
+    }
+}
+
+fn run_test(commands: Commands) -> Result<()> {
+    let args = ARGS.get().unwrap();
+
+    let mut test = match &args.reference {
+        Some(reference) => {
+            CommandsTest::new(args.dir.clone(), reference.clone(), args.seed)?
+        }
+        None => CommandsTest::with_model(args.dir.clone(), args.seed)?,
+    };
+
+    count_mix(&commands.0);
+    let ret = test.run(commands.0.clone());
     match ret {
         Ok(_) => eprintln!("Success"),
-        Err(_) => {
+        Err(ref e) => {
             eprintln!("\n***************");
             eprintln!("*   FAILURE   *");
-            eprintln!("***************\n")
+            eprintln!("***************\n");
+            eprintln!("{e}\n");
+            *LAST_FAILURE.lock().unwrap() = Some(commands.0);
         }
     }
     ret
 }
 
 fn main() -> Result<()> {
-    let args = std::env::args().collect::<Vec<_>>();
-    if args.len() != 4 {
-        return Err(Error::new(
-            ErrorKind::Other,
-            format!("usage: {} DIR1 DIR2 MAX_FILE_SIZE_MB", args[0]),
-        ));
-    }
+    let args = ARGS.get_or_init(|| match Args::parse() {
+        Ok(args) => args,
+        Err(ref e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    });
 
-    let size = args[3].parse::<usize>().expect("Invalid maximum file size");
+    eprintln!("Seed: {}", args.seed);
 
     // The division by two is because for a max file size, we could technically
     // generate a MAX_FILE_SIZE write at offset MAX_FILE_SIZE though that'd be
     // unlikely to reach that exactly.
-    MAX_FILE_SIZE.get_or_init(|| (size * 1024 * 1024) / 2);
+    MAX_FILE_SIZE.get_or_init(|| (args.max_file_size * 1024 * 1024) / 2);
+    EQUIVALENT_ERRNOS.get_or_init(|| args.equivalent.clone());
+    COMMAND_WEIGHTS.get_or_init(|| args.weights.clone());
+
+    // The generator size bounds the length of the generated command lists.
+    let g = Gen::from_size_and_seed(args.max_commands, args.seed);
+    let ret = QuickCheck::new()
+        .rng(g)
+        .tests(args.tests)
+        .max_tests(args.tests.max(10000))
+        .quicktest(run_test as fn(_) -> _);
 
-    quickcheck(run_test as fn(_) -> _);
+    print_mix();
+
+    if ret.is_err() {
+        let commands = LAST_FAILURE.lock().unwrap().take().unwrap_or_default();
+        eprintln!("\nMinimized failure (seed {}):", args.seed);
+        for cmd in commands.iter() {
+            eprintln!("{cmd:?}");
+        }
+
+        let regression = Regression {
+            seed: args.seed,
+            commands,
+        };
+        let path = save_regression(&args.regressions, &regression)?;
+        eprintln!("\nSaved regression to {}", path.display());
+        return Err(Error::other("Filesystems behaved differently"));
+    }
 
-    eprintln!("\nSuccess!");
+    eprintln!("\nSuccess! (seed {})", args.seed);
     Ok(())
 }
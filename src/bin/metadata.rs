@@ -0,0 +1,13 @@
+// Print the run metadata record for the configured test root. This is meant
+// to be written as the first line of a results file, see `make results`.
+
+use std::io::Result;
+
+use fstesting::metadata::RunMetadata;
+
+fn main() -> Result<()> {
+    let root = fstesting::test_root();
+    let metadata = RunMetadata::collect(&root);
+    println!("{}", serde_json::to_string(&metadata)?);
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// Generate the compatibility report or a CSV summary from a directory of
+// results files, see `make results`. Single results files can also be
+// checked against, or turned into, an expected-behavior profile.
+
+use std::collections::BTreeMap;
+use std::io::{Error, Result};
+use std::path::Path;
+
+use fstesting::profile::Profile;
+use fstesting::report::{self, Outcome, ReportDefs, Results};
+
+fn usage(prog: &str) -> Error {
+    Error::other(format!(
+        "usage: {prog} markdown REPORT_DEFS RESULTS_DIRECTORY\n       \
+         {prog} csv RESULTS_DIRECTORY\n       \
+         {prog} check PROFILE RESULTS_FILE\n       \
+         {prog} profile NAME RESULTS_FILE"
+    ))
+}
+
+fn load_outcomes(path: &Path) -> Result<BTreeMap<String, Outcome>> {
+    let mut results = Results::default();
+    results.load_file("run", path)?;
+    Ok(results.outcomes("run"))
+}
+
+fn check(profile: &Profile, outcomes: &BTreeMap<String, Outcome>) -> bool {
+    let cmp = profile.compare(outcomes);
+
+    println!("Profile: {}", profile.name);
+    println!("Tests: {}", outcomes.len());
+
+    if !cmp.newly_passing.is_empty() {
+        println!("\nNewly passing:");
+        for (tname, expected) in cmp.newly_passing.iter() {
+            println!("  {tname} (expected {expected})");
+        }
+    }
+
+    if !cmp.newly_failing.is_empty() {
+        println!("\nNewly failing:");
+        for (tname, outcome) in cmp.newly_failing.iter() {
+            println!("  {tname} ({outcome})");
+        }
+    }
+
+    if !cmp.changed.is_empty() {
+        println!("\nChanged:");
+        for (tname, expected, outcome) in cmp.changed.iter() {
+            println!("  {tname} (expected {expected}, got {outcome})");
+        }
+    }
+
+    if !cmp.missing.is_empty() {
+        println!("\nNot run:");
+        for tname in cmp.missing.iter() {
+            println!("  {tname}");
+        }
+    }
+
+    if cmp.is_expected() {
+        println!("\nAll results match the profile.");
+    }
+
+    cmp.is_expected()
+}
+
+fn main() -> Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    match args.get(1).map(|a| a.as_str()) {
+        Some("markdown") if args.len() == 4 => {
+            let defs = ReportDefs::load(Path::new(&args[2]))?;
+            let results = Results::load(Path::new(&args[3]))?;
+            let info =
+                report::load_test_info(&report::source_root(), &results)?;
+            let sha = env!("FSTESTING_REVISION");
+
+            println!("{}", report::markdown(&defs, &results, &info, sha));
+        }
+        Some("csv") if args.len() == 3 => {
+            let results = Results::load(Path::new(&args[2]))?;
+            print!("{}", report::csv(&results));
+        }
+        Some("check") if args.len() == 4 => {
+            let profile = Profile::load(Path::new(&args[2]))?;
+            let outcomes = load_outcomes(Path::new(&args[3]))?;
+            if !check(&profile, &outcomes) {
+                std::process::exit(1);
+            }
+        }
+        Some("profile") if args.len() == 4 => {
+            let outcomes = load_outcomes(Path::new(&args[3]))?;
+            let profile = Profile::from_outcomes(&args[2], &outcomes);
+            print!("{}", profile.to_yaml()?);
+        }
+        _ => return Err(usage(&args[0])),
+    }
+
+    Ok(())
+}
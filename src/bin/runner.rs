@@ -0,0 +1,152 @@
+// Run the test suite one test at a time with a timeout per test and print the
+// results as libtest JSON events, see `make results`. An optional argument
+// only runs the tests whose name contains it.
+//
+// With `--sizes` the data tests run once for each of the `BOUNDARY_SIZES`, or
+// for each size in a `--sizes=N,N,...` list. Their results are named
+// `NAME@SIZE`.
+
+use std::io::{Error, Result};
+use std::path::Path;
+use std::time::Instant;
+
+use serde_json::json;
+
+use fstesting::runner::{
+    BOUNDARY_SIZES, RunOutcome, Runner, is_data_test, timeout_for,
+};
+
+struct Args {
+    filter: Option<String>,
+    sizes: Option<Vec<usize>>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = std::env::args();
+        let prog = args.next().unwrap_or_default();
+        let usage = || {
+            Error::other(format!("usage: {prog} [--sizes[=N,N...]] [FILTER]"))
+        };
+
+        let mut filter = None;
+        let mut sizes = None;
+        for arg in args {
+            if arg == "--sizes" {
+                sizes = Some(BOUNDARY_SIZES.to_vec());
+            } else if let Some(list) = arg.strip_prefix("--sizes=") {
+                let list = list
+                    .split(',')
+                    .map(|size| {
+                        size.trim().parse::<usize>().map_err(|_| {
+                            Error::other(format!("Invalid size: {size}"))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                sizes = Some(list);
+            } else if arg.starts_with("--") || filter.is_some() {
+                return Err(usage());
+            } else {
+                filter = Some(arg);
+            }
+        }
+
+        Ok(Self { filter, sizes })
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let root = fstesting::test_root();
+    let runner = Runner::new(Path::new(env!("CARGO_MANIFEST_DIR")))?;
+    let tests = runner.list(args.filter.as_deref())?;
+
+    let start = Instant::now();
+    let (mut passed, mut failed, mut ignored, mut timed_out) = (0, 0, 0, 0);
+
+    println!(
+        "{}",
+        json!({"type": "suite", "event": "started", "test_count": tests.len()})
+    );
+
+    for test in tests.iter() {
+        let sizes = match &args.sizes {
+            Some(sizes) if is_data_test(&test.name) => {
+                sizes.iter().map(|size| Some(*size)).collect()
+            }
+            _ => vec![None],
+        };
+
+        for size in sizes {
+            let name = match size {
+                Some(size) => format!("{}@{size}", test.name),
+                None => test.name.clone(),
+            };
+
+            println!(
+                "{}",
+                json!({"type": "test", "event": "started", "name": name})
+            );
+
+            if test.ignored {
+                ignored += 1;
+                eprintln!("test {name} ... ignored");
+                println!(
+                    "{}",
+                    json!({"type": "test", "event": "ignored", "name": name})
+                );
+                continue;
+            }
+
+            let run = runner.run(&test.name, timeout_for(&test.name), size)?;
+            eprintln!("test {name} ... {}", run.outcome.event());
+
+            let mut event = json!({
+                "type": "test",
+                "event": run.outcome.event(),
+                "name": name,
+                "exec_time": run.exec_time.as_secs_f64(),
+            });
+
+            match run.outcome {
+                RunOutcome::Ok => passed += 1,
+                RunOutcome::Failed => failed += 1,
+                RunOutcome::Timeout => timed_out += 1,
+            }
+
+            if run.outcome != RunOutcome::Ok {
+                event["stdout"] = run.output.into();
+            }
+
+            println!("{event}");
+        }
+    }
+
+    let ok = failed == 0 && timed_out == 0;
+    println!(
+        "{}",
+        json!({
+            "type": "suite",
+            "event": if ok { "ok" } else { "failed" },
+            "passed": passed,
+            "failed": failed,
+            "ignored": ignored,
+            "timeout": timed_out,
+            "exec_time": start.elapsed().as_secs_f64(),
+        })
+    );
+
+    // Tests that failed keep their directories, which keeps this one too.
+    let _ = std::fs::remove_dir(root.join(&runner.run_dir));
+
+    eprintln!(
+        "\n{passed} passed, {failed} failed, {timed_out} timed out, \
+         {ignored} ignored"
+    );
+
+    if !ok {
+        return Err(Error::other("Some tests failed"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// Runtime configuration for the test suite.
+//
+// Settings are read from a simple `key = value` config file and can be
+// overridden individually by environment variables named `FSTESTING_<KEY>`.
+// The config file defaults to `./fstesting.conf` and can be moved with the
+// `FSTESTING_CONFIG` environment variable.
+
+use std::collections::HashMap;
+use std::path;
+use std::str::FromStr;
+use std::sync;
+use std::time::Duration;
+
+const DEFAULT_CONFIG: &str = "./fstesting.conf";
+const DEFAULT_ROOT: &str = "./mountpoint";
+const DEFAULT_DATA_SIZE: usize = 1024 * 1024 * 15;
+const DEFAULT_TIMEOUT: u64 = 60;
+const DEFAULT_DATA_TIMEOUT: u64 = 600;
+
+static CONFIG: sync::OnceLock<Config> = sync::OnceLock::new();
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Directory under which all test directories are created.
+    pub root: path::PathBuf,
+    /// Require `root` to be the root of a mounted filesystem.
+    pub require_mount: bool,
+    /// Name of the run directory under `root`, random if not set.
+    pub run_dir: Option<path::PathBuf>,
+    /// Which test directories are left behind when a test finishes.
+    pub keep: Keep,
+    /// Size in bytes of the files used by the read and write tests.
+    pub data_size: usize,
+    /// How long the runner lets a single test run before killing it.
+    pub timeout: Duration,
+    /// The timeout for tests that transfer `data_size` bytes of data.
+    pub data_timeout: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keep {
+    Never,
+    Failed,
+    Always,
+}
+
+impl FromStr for Keep {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self::Never),
+            "failed" => Ok(Self::Failed),
+            "always" => Ok(Self::Always),
+            _ => Err(format!("Invalid keep mode: {s}")),
+        }
+    }
+}
+
+impl Config {
+    fn load() -> Self {
+        let settings = Settings::load();
+
+        Self {
+            root: settings
+                .get("root")
+                .map(path::PathBuf::from)
+                .unwrap_or_else(|| path::PathBuf::from(DEFAULT_ROOT)),
+            require_mount: settings.parse("require_mount").unwrap_or(true),
+            run_dir: settings.get("run_dir").map(path::PathBuf::from),
+            keep: settings.parse("keep").unwrap_or(Keep::Failed),
+            data_size: settings.parse("data_size").unwrap_or(DEFAULT_DATA_SIZE),
+            timeout: Duration::from_secs(
+                settings.parse("timeout").unwrap_or(DEFAULT_TIMEOUT),
+            ),
+            data_timeout: Duration::from_secs(
+                settings
+                    .parse("data_timeout")
+                    .unwrap_or(DEFAULT_DATA_TIMEOUT),
+            ),
+        }
+    }
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::load)
+}
+
+struct Settings {
+    source: String,
+    values: HashMap<String, String>,
+}
+
+impl Settings {
+    fn load() -> Self {
+        let (source, required) = match std::env::var("FSTESTING_CONFIG") {
+            Ok(fname) => (fname, true),
+            Err(_) => (DEFAULT_CONFIG.to_owned(), false),
+        };
+
+        let contents = match std::fs::read_to_string(&source) {
+            Ok(contents) => contents,
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => {
+                String::new()
+            }
+            Err(e) => panic!("Error reading config file {source}: {e}"),
+        };
+
+        let mut values = HashMap::new();
+        for (lineno, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                panic!(
+                    "Invalid config line {}:{}: expected `key = value`",
+                    source,
+                    lineno + 1
+                );
+            };
+
+            values.insert(key.trim().to_owned(), value.trim().to_owned());
+        }
+
+        Self { source, values }
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        let var = format!("FSTESTING_{}", key.to_uppercase());
+        std::env::var(var)
+            .ok()
+            .or_else(|| self.values.get(key).cloned())
+    }
+
+    fn parse<T: FromStr>(&self, key: &str) -> Option<T> {
+        let value = self.get(key)?;
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(_) => panic!(
+                "Invalid value for `{key}` (from {} or FSTESTING_{}): {value}",
+                self.source,
+                key.to_uppercase()
+            ),
+        }
+    }
+}
@@ -1,5 +1,10 @@
 pub mod commands;
+pub mod config;
 pub mod enums;
+pub mod metadata;
+pub mod profile;
+pub mod report;
+pub mod runner;
 pub mod test_path;
 pub mod tests;
 pub mod utils;
@@ -0,0 +1,328 @@
+// Describe the environment a test run was executed in so that results from
+// different machines can be compared. This is emitted as the first record of
+// a results file, before the libtest JSON events.
+
+use std::ffi::CStr;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::test_path::TestPath;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(tag = "type", rename = "metadata")]
+pub struct RunMetadata {
+    /// The test root the run was executed against.
+    pub root: String,
+    /// The filesystem magic number reported by `statfs`.
+    pub fs_magic: u64,
+    /// The filesystem name decoded from `fs_magic`.
+    pub fs_type: String,
+    /// The optimal transfer block size reported by `statfs`.
+    pub block_size: u64,
+    /// The mount entry that contains the test root.
+    pub mount: Option<MountInfo>,
+    /// Operating system name and release, as reported by `uname`.
+    pub kernel: String,
+    /// The fstesting git revision the tests were built from.
+    pub revision: String,
+    /// How the filesystem treats case and unicode normalization in names.
+    #[serde(default)]
+    pub names: Option<NameBehavior>,
+    /// The granularity of file timestamps in nanoseconds.
+    #[serde(default)]
+    pub timestamp_granularity: Option<u64>,
+    /// The `statx` fields and attributes the filesystem reports for a file.
+    #[serde(default)]
+    pub statx: Option<StatxSupport>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MountInfo {
+    pub mount_point: String,
+    pub fs_type: String,
+    pub source: String,
+    pub mount_options: String,
+    pub super_options: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NameBehavior {
+    /// Whether `a` and `A` name different files.
+    pub case_sensitive: bool,
+    /// Whether the case a name was created with is kept in listings.
+    pub case_preserving: bool,
+    /// One of `distinct` when NFC and NFD forms of a name are different
+    /// files, `equivalent` when either form finds the same file, or `nfc` or
+    /// `nfd` when names are also converted to that form.
+    pub normalization: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct StatxSupport {
+    /// The `stx_mask` returned when asking for every field.
+    pub mask: u32,
+    /// The `stx_attributes_mask`, i.e. the attributes the filesystem supports.
+    pub attributes_mask: u64,
+}
+
+/// Names of the `STATX_*` fields beyond `STATX_BASIC_STATS`, from
+/// linux/stat.h. These are listed here so reports can be generated anywhere.
+pub const STATX_FIELDS: &[(u32, &str)] = &[
+    (0x0800, "btime"),
+    (0x1000, "mnt_id"),
+    (0x2000, "dioalign"),
+    (0x8000, "subvol"),
+    (0x10000, "write_atomic"),
+    (0x20000, "dio_read_align"),
+];
+
+/// Names of the `STATX_ATTR_*` attributes, from linux/stat.h.
+pub const STATX_ATTRIBUTES: &[(u64, &str)] = &[
+    (0x0004, "compressed"),
+    (0x0010, "immutable"),
+    (0x0020, "append"),
+    (0x0040, "nodump"),
+    (0x0800, "encrypted"),
+    (0x1000, "automount"),
+    (0x2000, "mount_root"),
+    (0x100000, "verity"),
+    (0x200000, "dax"),
+    (0x400000, "write_atomic"),
+];
+
+impl RunMetadata {
+    pub fn collect(root: &Path) -> Self {
+        let mut path = TestPath::from(root.to_path_buf());
+        let stats = crate::statfs(&mut path);
+
+        Self {
+            root: root.display().to_string(),
+            fs_magic: stats.f_type as u64,
+            fs_type: fs_type_name(&stats),
+            block_size: stats.f_bsize as u64,
+            mount: mount_info(root),
+            kernel: kernel_version(),
+            revision: env!("FSTESTING_REVISION").to_owned(),
+            names: name_behavior(),
+            timestamp_granularity: timestamp_granularity(),
+            statx: statx_support(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn fs_type_name(stats: &libc::statfs) -> String {
+    let name = match stats.f_type as u64 {
+        0x00C36400 => "ceph",
+        0x01021994 => "tmpfs",
+        0x2011BAB0 => "exfat",
+        0x2FC12FC1 => "zfs",
+        0x4D44 => "vfat",
+        0x5346544E => "ntfs",
+        0x58465342 => "xfs",
+        0x65735546 => "fuse",
+        0x6969 => "nfs",
+        0x73717368 => "squashfs",
+        0x794C7630 => "overlayfs",
+        0x858458F6 => "ramfs",
+        0x9123683E => "btrfs",
+        0xCA451A4E => "bcachefs",
+        0xEF53 => "ext2/ext3/ext4",
+        0xF2F52010 => "f2fs",
+        0xFE534D42 => "smb2",
+        0xFF534D42 => "cifs",
+        _ => "unknown",
+    };
+
+    name.to_owned()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn fs_type_name(stats: &libc::statfs) -> String {
+    let name = unsafe { CStr::from_ptr(stats.f_fstypename.as_ptr()) };
+    name.to_string_lossy().to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn mount_info(root: &Path) -> Option<MountInfo> {
+    let root = std::fs::canonicalize(root).ok()?;
+    let contents = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+
+    // Later entries shadow earlier ones mounted at the same location so the
+    // last, longest matching mount point is the one that contains the root.
+    let mut found: Option<MountInfo> = None;
+    for line in contents.lines() {
+        let Some(info) = parse_mountinfo(line) else {
+            continue;
+        };
+
+        if !root.starts_with(&info.mount_point) {
+            continue;
+        }
+
+        let longer = found
+            .as_ref()
+            .map(|f| info.mount_point.len() >= f.mount_point.len())
+            .unwrap_or(true);
+        if longer {
+            found = Some(info);
+        }
+    }
+
+    found
+}
+
+#[cfg(not(target_os = "linux"))]
+fn mount_info(_root: &Path) -> Option<MountInfo> {
+    None
+}
+
+// Format described in proc(5):
+//
+// 36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+#[cfg(target_os = "linux")]
+fn parse_mountinfo(line: &str) -> Option<MountInfo> {
+    let (mount, sb) = line.split_once(" - ")?;
+
+    let mut mount = mount.split(' ');
+    let mount_point = mount.nth(4)?;
+    let mount_options = mount.next()?;
+
+    let mut sb = sb.split(' ');
+    let fs_type = sb.next()?;
+    let source = sb.next()?;
+    let super_options = sb.next()?;
+
+    Some(MountInfo {
+        mount_point: unescape_octal(mount_point),
+        fs_type: fs_type.to_owned(),
+        source: unescape_octal(source),
+        mount_options: mount_options.to_owned(),
+        super_options: super_options.to_owned(),
+    })
+}
+
+// Spaces, tabs, newlines and backslashes are escaped as `\NNN` octal.
+#[cfg(target_os = "linux")]
+fn unescape_octal(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut ret = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'\\' && idx + 4 <= bytes.len() {
+            let digits = std::str::from_utf8(&bytes[(idx + 1)..(idx + 4)]);
+            if let Some(byte) =
+                digits.ok().and_then(|d| u8::from_str_radix(d, 8).ok())
+            {
+                ret.push(byte);
+                idx += 4;
+                continue;
+            }
+        }
+
+        ret.push(bytes[idx]);
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&ret).to_string()
+}
+
+// Probe name handling in a scratch test directory, which is removed with the
+// run directory. Returns None if the probe files can't be created, e.g. on a
+// read-only root.
+fn name_behavior() -> Option<NameBehavior> {
+    let dir = crate::try_test_dir().ok()?;
+    probe_names(dir.as_ref())
+}
+
+fn probe_names(dir: &Path) -> Option<NameBehavior> {
+    const NFC: &str = "\u{e9}";
+    const NFD: &str = "e\u{301}";
+
+    let list = |dir: &Path| -> Option<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(dir).ok()? {
+            names.push(entry.ok()?.file_name().to_string_lossy().to_string());
+        }
+        Some(names)
+    };
+
+    let case = dir.join("case");
+    std::fs::create_dir(&case).ok()?;
+    std::fs::File::create(case.join("Name")).ok()?;
+    let case_sensitive = std::fs::symlink_metadata(case.join("NAME")).is_err();
+    let case_preserving = list(&case)? == ["Name"];
+
+    let nfc = dir.join("nfc");
+    std::fs::create_dir(&nfc).ok()?;
+    std::fs::File::create(nfc.join(NFC)).ok()?;
+
+    let nfd = dir.join("nfd");
+    std::fs::create_dir(&nfd).ok()?;
+    std::fs::File::create(nfd.join(NFD)).ok()?;
+
+    let normalization = if list(&nfd)? == [NFC] {
+        "nfc"
+    } else if list(&nfc)? == [NFD] {
+        "nfd"
+    } else if std::fs::symlink_metadata(nfc.join(NFD)).is_ok() {
+        "equivalent"
+    } else {
+        "distinct"
+    };
+
+    Some(NameBehavior {
+        case_sensitive,
+        case_preserving,
+        normalization: normalization.to_owned(),
+    })
+}
+
+fn timestamp_granularity() -> Option<u64> {
+    let mut dir = crate::try_test_dir().ok()?;
+    dir.push("times");
+    std::fs::File::create(&*dir).ok()?;
+    crate::timestamp_granularity(&mut dir)
+}
+
+#[cfg(target_os = "linux")]
+fn statx_support() -> Option<StatxSupport> {
+    let mut dir = crate::try_test_dir().ok()?;
+    dir.push("statx");
+    std::fs::File::create(&*dir).ok()?;
+
+    let mask = STATX_FIELDS
+        .iter()
+        .fold(libc::STATX_BASIC_STATS, |mask, (field, _)| mask | field);
+
+    let mut stx: libc::statx = unsafe { std::mem::zeroed() };
+    let err =
+        unsafe { libc::statx(libc::AT_FDCWD, dir.c_str(), 0, mask, &mut stx) };
+
+    (err == 0).then_some(StatxSupport {
+        mask: stx.stx_mask,
+        attributes_mask: stx.stx_attributes_mask,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn statx_support() -> Option<StatxSupport> {
+    None
+}
+
+fn kernel_version() -> String {
+    unsafe {
+        let mut uts: libc::utsname = std::mem::zeroed();
+        if libc::uname(&mut uts) != 0 {
+            return "unknown".to_owned();
+        }
+
+        let sysname = CStr::from_ptr(uts.sysname.as_ptr()).to_string_lossy();
+        let release = CStr::from_ptr(uts.release.as_ptr()).to_string_lossy();
+        let version = CStr::from_ptr(uts.version.as_ptr()).to_string_lossy();
+
+        format!("{sysname} {release} {version}")
+    }
+}
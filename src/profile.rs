@@ -0,0 +1,110 @@
+// Expected test outcomes for a named filesystem. Comparing a run against its
+// profile separates known limitations, like a filesystem that never supports
+// `rename`, from actual regressions.
+
+use std::collections::BTreeMap;
+use std::io::{Error, Result};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::Outcome;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Profile {
+    /// The filesystem this profile describes.
+    pub name: String,
+    /// The expected outcome of any test not listed in `tests`.
+    #[serde(default = "default_outcome")]
+    pub default: Outcome,
+    /// Expected outcomes by test name.
+    #[serde(default)]
+    pub tests: BTreeMap<String, Outcome>,
+}
+
+fn default_outcome() -> Outcome {
+    Outcome::Pass
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Comparison {
+    /// Tests that passed but were expected not to.
+    pub newly_passing: Vec<(String, Outcome)>,
+    /// Tests that were expected to pass but did not.
+    pub newly_failing: Vec<(String, Outcome)>,
+    /// Tests that neither passed nor were expected to, but with a different
+    /// outcome than expected, e.g. failed instead of unsupported.
+    pub changed: Vec<(String, Outcome, Outcome)>,
+    /// Tests listed in the profile that have no result.
+    pub missing: Vec<String>,
+}
+
+impl Comparison {
+    pub fn is_expected(&self) -> bool {
+        self.newly_passing.is_empty()
+            && self.newly_failing.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl Profile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents).map_err(|e| {
+            Error::other(format!("Invalid profile {}: {e}", path.display()))
+        })
+    }
+
+    /// Create a profile that expects exactly the given outcomes.
+    pub fn from_outcomes(
+        name: &str,
+        outcomes: &BTreeMap<String, Outcome>,
+    ) -> Self {
+        let tests = outcomes
+            .iter()
+            .filter(|(_, outcome)| **outcome != Outcome::Pass)
+            .map(|(tname, outcome)| (tname.clone(), *outcome))
+            .collect();
+
+        Self {
+            name: name.to_owned(),
+            default: Outcome::Pass,
+            tests,
+        }
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(Error::other)
+    }
+
+    pub fn expected(&self, tname: &str) -> Outcome {
+        self.tests.get(tname).copied().unwrap_or(self.default)
+    }
+
+    pub fn compare(&self, outcomes: &BTreeMap<String, Outcome>) -> Comparison {
+        let mut cmp = Comparison::default();
+
+        for (tname, outcome) in outcomes.iter() {
+            let expected = self.expected(tname);
+            if *outcome == expected {
+                continue;
+            }
+
+            if *outcome == Outcome::Pass {
+                cmp.newly_passing.push((tname.clone(), expected));
+            } else if expected == Outcome::Pass {
+                cmp.newly_failing.push((tname.clone(), *outcome));
+            } else {
+                cmp.changed.push((tname.clone(), expected, *outcome));
+            }
+        }
+
+        for tname in self.tests.keys() {
+            if !outcomes.contains_key(tname) {
+                cmp.missing.push(tname.clone());
+            }
+        }
+
+        cmp
+    }
+}
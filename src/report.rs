@@ -0,0 +1,658 @@
+// Generate the filesystem compatibility report from a directory of results
+// files. Each file holds the libtest JSON output of one test run and the
+// file name (without extension) is used as the filesystem name.
+
+use std::collections::BTreeMap;
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::metadata::{
+    NameBehavior, RunMetadata, STATX_ATTRIBUTES, STATX_FIELDS, StatxSupport,
+};
+
+pub const PASS: &str = "\u{2705}";
+pub const SKIP: &str = "\u{26a0}";
+pub const FAIL: &str = "\u{274c}";
+pub const TIMEOUT: &str = "\u{23f1}";
+pub const MISSING: &str = "-";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// The filesystem reported the operation under test as unsupported.
+    Unsupported,
+    /// The test was killed by the runner after exceeding its timeout.
+    Timeout,
+}
+
+impl Outcome {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Pass => PASS,
+            Self::Fail => FAIL,
+            Self::Unsupported => SKIP,
+            Self::Timeout => TIMEOUT,
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+            Self::Unsupported => "unsupported",
+            Self::Timeout => "timeout",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReportDefs {
+    pub repo: String,
+    pub prelude: String,
+    pub topics: BTreeMap<String, String>,
+}
+
+impl ReportDefs {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents).map_err(|e| {
+            Error::other(format!("Invalid report defs {}: {e}", path.display()))
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Results {
+    /// Run metadata by filesystem name, if recorded.
+    pub metadata: BTreeMap<String, RunMetadata>,
+    /// Test outcomes by test name and then filesystem name.
+    pub tests: BTreeMap<String, BTreeMap<String, Outcome>>,
+}
+
+#[derive(Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: String,
+    event: Option<String>,
+    name: Option<String>,
+    stdout: Option<String>,
+}
+
+impl Results {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut results = Self::default();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let Some(fsname) = path.file_stem() else {
+                continue;
+            };
+            let fsname = fsname.to_string_lossy().to_string();
+
+            results.load_file(&fsname, &path)?;
+        }
+
+        Ok(results)
+    }
+
+    pub fn load_file(&mut self, fsname: &str, path: &Path) -> Result<()> {
+        let contents = std::fs::read_to_string(path)?;
+
+        for (lineno, line) in contents.lines().enumerate() {
+            let invalid = |e: serde_json::Error| {
+                Error::other(format!(
+                    "Invalid result {}:{}: {e}",
+                    path.display(),
+                    lineno + 1
+                ))
+            };
+
+            let event: Event = serde_json::from_str(line).map_err(invalid)?;
+
+            if event.kind == "metadata" {
+                let metadata = serde_json::from_str(line).map_err(invalid)?;
+                self.metadata.insert(fsname.to_owned(), metadata);
+                continue;
+            }
+
+            if event.kind != "test" {
+                continue;
+            }
+
+            let unsupported = event
+                .stdout
+                .as_ref()
+                .is_some_and(|out| out.contains(crate::UNSUPPORTED));
+
+            let (Some(name), Some(event)) = (event.name, event.event) else {
+                continue;
+            };
+
+            let outcome = match event.as_str() {
+                "ok" => Outcome::Pass,
+                "failed" if unsupported => Outcome::Unsupported,
+                "failed" => Outcome::Fail,
+                "timeout" => Outcome::Timeout,
+                _ => continue,
+            };
+
+            // Data tests run at several sizes are recorded as `NAME@SIZE`
+            // and only pass if they passed at every size.
+            let name = name.split('@').next().unwrap_or(&name);
+            let name = name.rsplit("::").next().unwrap_or(name).to_owned();
+            let outcomes = self.tests.entry(name).or_default();
+            if outcomes.get(fsname).is_none_or(|o| *o == Outcome::Pass) {
+                outcomes.insert(fsname.to_owned(), outcome);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn fsnames(&self) -> Vec<String> {
+        let mut fsnames = self
+            .tests
+            .values()
+            .flat_map(|v| v.keys().cloned())
+            .collect::<Vec<_>>();
+        fsnames.sort();
+        fsnames.dedup();
+        fsnames
+    }
+
+    /// Test outcomes for a single filesystem.
+    pub fn outcomes(&self, fsname: &str) -> BTreeMap<String, Outcome> {
+        self.tests
+            .iter()
+            .filter_map(|(tname, v)| Some((tname.clone(), *v.get(fsname)?)))
+            .collect()
+    }
+
+    pub fn topic_tests(&self, topic: &str) -> Vec<&String> {
+        self.tests
+            .keys()
+            .filter(|tname| test_topic(tname) == topic)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum TestInfo {
+    /// A test generated by one of the `open_*` macros.
+    OpenMacro {
+        fname: String,
+        lineno: usize,
+        perms: String,
+        opts: String,
+        error: Option<String>,
+    },
+    /// A test function with a `/// name: description` doc comment.
+    TestFn {
+        fname: String,
+        lineno: usize,
+        desc: String,
+    },
+}
+
+impl TestInfo {
+    pub fn source(&self) -> (&str, usize) {
+        match self {
+            Self::OpenMacro { fname, lineno, .. } => (fname, *lineno),
+            Self::TestFn { fname, lineno, .. } => (fname, *lineno),
+        }
+    }
+}
+
+/// Extract test descriptions from the test sources in `root/src/tests`.
+pub fn load_test_info(
+    root: &Path,
+    results: &Results,
+) -> Result<BTreeMap<String, TestInfo>> {
+    let mut sources = Vec::new();
+    load_sources(root, &root.join("src/tests"), &mut sources)?;
+    sources.sort();
+
+    let mut info = BTreeMap::new();
+    for tname in results.tests.keys() {
+        let tinfo = if is_open_macro(tname) {
+            load_macro_open(tname, &sources)?
+        } else {
+            load_test_fn(tname, &sources)?
+        };
+        info.insert(tname.clone(), tinfo);
+    }
+
+    Ok(info)
+}
+
+type Source = (String, Vec<String>);
+
+fn load_sources(
+    root: &Path,
+    dir: &Path,
+    sources: &mut Vec<Source>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            load_sources(root, &path, sources)?;
+            continue;
+        }
+
+        if path.extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+
+        let fname = path.strip_prefix(root).unwrap_or(&path);
+        let fname = fname.display().to_string();
+        let lines = std::fs::read_to_string(&path)?
+            .lines()
+            .map(|l| l.to_owned())
+            .collect();
+        sources.push((fname, lines));
+    }
+
+    Ok(())
+}
+
+fn is_open_macro(tname: &str) -> bool {
+    tname.starts_with("open_") && test_topic(tname).contains('_')
+}
+
+fn test_topic(tname: &str) -> &str {
+    tname.rsplit_once('_').map(|(t, _)| t).unwrap_or(tname)
+}
+
+// Macro tests are defined as `open_ne_01: (omode, oflags, success, errno),`
+fn load_macro_open(tname: &str, sources: &[Source]) -> Result<TestInfo> {
+    let prefix = format!("{tname}:");
+
+    for (fname, lines) in sources {
+        for (idx, line) in lines.iter().enumerate() {
+            // Definitions that were commented out still describe the test
+            // in older results files.
+            let line = line.trim().trim_start_matches("//");
+            let Some(args) = line.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            let args =
+                args.trim().trim_start_matches('(').trim_end_matches("),");
+            let bits =
+                args.splitn(4, ',').map(|b| b.trim()).collect::<Vec<_>>();
+            if bits.len() != 4 {
+                return Err(Error::other(format!(
+                    "Bad test definition {fname}:{}: {line}",
+                    idx + 1
+                )));
+            }
+
+            let error = if bits[2] == "true" {
+                None
+            } else {
+                Some(bits[3].replace("libc::", ""))
+            };
+
+            return Ok(TestInfo::OpenMacro {
+                fname: fname.clone(),
+                lineno: idx + 1,
+                perms: bits[0].replace("libc::", ""),
+                opts: bits[1].replace("libc::", ""),
+                error,
+            });
+        }
+    }
+
+    Err(Error::other(format!(
+        "Error locating test info for {tname}"
+    )))
+}
+
+fn load_test_fn(tname: &str, sources: &[Source]) -> Result<TestInfo> {
+    let doc = format!("/// {tname}:");
+    let func = format!("fn {tname}(");
+
+    for (fname, lines) in sources {
+        let mut desc = None;
+        let mut lineno = None;
+
+        for (idx, line) in lines.iter().enumerate() {
+            if let Some(d) = line.strip_prefix(&doc) {
+                desc = Some(d.trim().to_owned());
+            } else if line.starts_with(&func) {
+                lineno = Some(idx + 1);
+            }
+        }
+
+        match (desc, lineno) {
+            (Some(desc), Some(lineno)) => {
+                return Ok(TestInfo::TestFn {
+                    fname: fname.clone(),
+                    lineno,
+                    desc,
+                });
+            }
+            (None, Some(_)) => {
+                return Err(Error::other(format!(
+                    "Missing `{doc}` description for {tname} in {fname}"
+                )));
+            }
+            _ => (),
+        }
+    }
+
+    Err(Error::other(format!(
+        "Error locating test info for {tname}"
+    )))
+}
+
+/// Generate the Markdown compatibility report.
+pub fn markdown(
+    defs: &ReportDefs,
+    results: &Results,
+    info: &BTreeMap<String, TestInfo>,
+    sha: &str,
+) -> String {
+    let mut parts = vec![defs.prelude.clone(), "\n".to_owned()];
+    parts.push(generate_overview(defs, results));
+    parts.push(generate_environments(results));
+
+    for topic in defs.topics.keys() {
+        let lines = if topic.starts_with("open") && topic.contains('_') {
+            generate_macro_open_topic(topic, defs, results, info, sha)
+        } else {
+            generate_test_fn_topic(topic, defs, results, info, sha)
+        };
+        parts.push(lines.join("\n"));
+    }
+
+    parts.concat()
+}
+
+fn generate_overview(defs: &ReportDefs, results: &Results) -> String {
+    let fsnames = results.fsnames();
+
+    let mut rows = Vec::new();
+    let mut header = vec!["Topic".to_owned(), "Num. Tests".to_owned()];
+    header.extend(fsnames.iter().cloned());
+    rows.push(header);
+
+    let mut align = vec!["-----".to_owned()];
+    align.extend(vec!["-----:".to_owned(); 1 + fsnames.len()]);
+    rows.push(align);
+
+    for topic in defs.topics.keys() {
+        let tnames = results.topic_tests(topic);
+
+        let mut row = vec![format!("[{topic}](#{topic})")];
+        row.push(tnames.len().to_string());
+        for fsname in fsnames.iter() {
+            let count = tnames
+                .iter()
+                .filter(|tname| {
+                    results.tests[**tname].get(fsname) == Some(&Outcome::Pass)
+                })
+                .count();
+            row.push(count.to_string());
+        }
+        rows.push(row);
+    }
+
+    let mut ret = vec!["## Summary Results".to_owned(), "".to_owned()];
+    ret.extend(rows.iter().map(|row| format_row(row)));
+    ret.push("".to_owned());
+    ret.push(format!(
+        "{PASS} passed, {FAIL} failed, {SKIP} unsupported, \
+         {TIMEOUT} timed out, {MISSING} not run"
+    ));
+    ret.push("".to_owned());
+    ret.join("\n")
+}
+
+fn name_summary(names: &NameBehavior) -> String {
+    let case = match (names.case_sensitive, names.case_preserving) {
+        (true, _) => "case sensitive",
+        (false, true) => "case insensitive, preserving",
+        (false, false) => "case insensitive",
+    };
+    let normalization = match names.normalization.as_str() {
+        "distinct" => "NFC and NFD distinct",
+        "equivalent" => "NFC and NFD equivalent",
+        "nfc" => "normalized to NFC",
+        "nfd" => "normalized to NFD",
+        other => other,
+    };
+    format!("{case}; {normalization}")
+}
+
+fn granularity_summary(nanos: u64) -> String {
+    let units = [(1_000_000_000, "s"), (1_000_000, "ms"), (1_000, "\u{b5}s")];
+    for (scale, unit) in units {
+        if nanos >= scale && nanos.is_multiple_of(scale) {
+            return format!("{} {unit}", nanos / scale);
+        }
+    }
+    format!("{nanos} ns")
+}
+
+fn statx_summary(statx: &StatxSupport) -> String {
+    let fields = STATX_FIELDS
+        .iter()
+        .filter(|(field, _)| statx.mask & field != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+    let attributes = STATX_ATTRIBUTES
+        .iter()
+        .filter(|(attr, _)| statx.attributes_mask & attr != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+
+    let mut ret = if fields.is_empty() {
+        "basic".to_owned()
+    } else {
+        fields.join(", ")
+    };
+    if !attributes.is_empty() {
+        ret.push_str(&format!("; attributes: {}", attributes.join(", ")));
+    }
+    ret
+}
+
+fn generate_environments(results: &Results) -> String {
+    if results.metadata.is_empty() {
+        return String::new();
+    }
+
+    let mut lines = vec![
+        "".to_owned(),
+        "## Environments".to_owned(),
+        "".to_owned(),
+        "| Name | Type | Mount Options | Block Size | Names | Timestamps | \
+         statx | Kernel | Revision |"
+            .to_owned(),
+        "| ----- | ----- | ----- | -----: | ----- | ----- | ----- | ----- | \
+         ----- |"
+            .to_owned(),
+    ];
+
+    for (fsname, md) in results.metadata.iter() {
+        let (fs_type, options) = match &md.mount {
+            Some(mount) => {
+                (mount.fs_type.as_str(), mount.mount_options.as_str())
+            }
+            None => (md.fs_type.as_str(), ""),
+        };
+        lines.push(format_row(&[
+            fsname.clone(),
+            fs_type.to_owned(),
+            options.to_owned(),
+            md.block_size.to_string(),
+            md.names.as_ref().map(name_summary).unwrap_or_default(),
+            md.timestamp_granularity
+                .map(granularity_summary)
+                .unwrap_or_default(),
+            md.statx.as_ref().map(statx_summary).unwrap_or_default(),
+            md.kernel.clone(),
+            md.revision.clone(),
+        ]));
+    }
+
+    lines.push("".to_owned());
+    lines.join("\n")
+}
+
+fn generate_macro_open_topic(
+    topic: &str,
+    defs: &ReportDefs,
+    results: &Results,
+    info: &BTreeMap<String, TestInfo>,
+    sha: &str,
+) -> Vec<String> {
+    let mut lines = topic_header(topic, defs, results);
+    lines.push("".to_owned());
+
+    for tname in results.topic_tests(topic) {
+        let TestInfo::OpenMacro {
+            perms, opts, error, ..
+        } = &info[tname]
+        else {
+            continue;
+        };
+
+        let error = error.as_deref().unwrap_or("Success");
+
+        // The macro adds O_CREAT to the flags for these tests.
+        let opts = if topic == "open_creat" {
+            format!("O_CREAT | {opts}")
+        } else {
+            opts.clone()
+        };
+
+        let link = make_source_link(defs, &info[tname], sha);
+        lines.push(format!("### {tname}"));
+        lines.push(format!(
+            "[(top)](#summary-results) [(table)](#{topic}) [(source)]({link})"
+        ));
+        lines.push("".to_owned());
+        lines.push("| Arg | Value |".to_owned());
+        lines.push("| ----- | ----- |".to_owned());
+        lines.push(format!("| Permissions | {perms} |"));
+        lines.push(format!("| Options | {opts} |"));
+        lines.push(format!("| Result | {error} |"));
+        lines.push("".to_owned());
+    }
+
+    lines.push("".to_owned());
+    lines
+}
+
+fn generate_test_fn_topic(
+    topic: &str,
+    defs: &ReportDefs,
+    results: &Results,
+    info: &BTreeMap<String, TestInfo>,
+    sha: &str,
+) -> Vec<String> {
+    let mut lines = topic_header(topic, defs, results);
+
+    for tname in results.topic_tests(topic) {
+        let TestInfo::TestFn { desc, .. } = &info[tname] else {
+            continue;
+        };
+
+        let link = make_source_link(defs, &info[tname], sha);
+        lines.push(format!("### {tname}"));
+        lines.push(format!(
+            "[(top)](#summary-results) [(table)](#{topic}) [(source)]({link})"
+        ));
+        lines.push("".to_owned());
+        lines.push(desc.clone());
+        lines.push("".to_owned());
+    }
+
+    lines
+}
+
+fn topic_header(
+    topic: &str,
+    defs: &ReportDefs,
+    results: &Results,
+) -> Vec<String> {
+    let mut lines = vec![
+        "".to_owned(),
+        format!("## {topic}"),
+        "[(top)](#summary-results)".to_owned(),
+        "".to_owned(),
+        defs.topics[topic].clone(),
+        "".to_owned(),
+    ];
+    lines.extend(generate_test_result_table(topic, results));
+    lines.push("".to_owned());
+    lines
+}
+
+fn generate_test_result_table(topic: &str, results: &Results) -> Vec<String> {
+    let fsnames = results.fsnames();
+
+    let mut header = vec!["Test".to_owned()];
+    header.extend(fsnames.iter().cloned());
+
+    let mut align = vec!["-----".to_owned()];
+    align.extend(vec![":-----:".to_owned(); fsnames.len()]);
+
+    let mut ret = vec![format_row(&header), format_row(&align)];
+    for tname in results.topic_tests(topic) {
+        let mut row = vec![format!("[{tname}](#{tname})")];
+        for fsname in fsnames.iter() {
+            let outcome = results.tests[tname].get(fsname);
+            row.push(outcome.map(|o| o.symbol()).unwrap_or(MISSING).to_owned());
+        }
+        ret.push(format_row(&row));
+    }
+
+    ret
+}
+
+fn make_source_link(defs: &ReportDefs, info: &TestInfo, sha: &str) -> String {
+    let (fname, lineno) = info.source();
+    format!("{}/blob/{sha}/{fname}#L{lineno}", defs.repo)
+}
+
+fn format_row(row: &[String]) -> String {
+    format!("| {} |", row.join(" | "))
+}
+
+/// Generate a CSV file of test results by filesystem.
+pub fn csv(results: &Results) -> String {
+    let fsnames = results.fsnames();
+
+    let mut lines = vec![format!("test,{}", fsnames.join(","))];
+    for (tname, outcomes) in results.tests.iter() {
+        let mut row = vec![tname.as_str()];
+        for fsname in fsnames.iter() {
+            row.push(
+                outcomes.get(fsname).map(|o| o.symbol()).unwrap_or(MISSING),
+            );
+        }
+        lines.push(row.join(","));
+    }
+
+    lines.push("".to_owned());
+    lines.join("\n")
+}
+
+/// The repository root, used to locate the test sources.
+pub fn source_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
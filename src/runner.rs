@@ -0,0 +1,273 @@
+// Run the test suite with one process per test so that a test that hangs on
+// the filesystem under test can be killed without wedging the whole run. The
+// outcome of each test is written as a libtest JSON event. Tests that were
+// killed get a `timeout` event, which the report shows as timed out.
+
+use std::io::{Error, Read, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::config;
+
+/// Modules whose tests transfer `data_size` bytes and get the longer
+/// `data_timeout`.
+const DATA_MODULES: &[&str] =
+    &["file_pread", "file_pwrite", "file_read", "file_write"];
+
+const MIB: usize = 1024 * 1024;
+
+/// Data sizes around common boundaries: empty and single byte files, a page,
+/// and the 8 MiB multipart upload part size used by many object stores.
+pub const BOUNDARY_SIZES: &[usize] = &[
+    0,
+    1,
+    4095,
+    4096,
+    4097,
+    8 * MIB - 1,
+    8 * MIB,
+    8 * MIB + 1,
+    15 * MIB,
+];
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// How long to wait for a killed test to exit before giving up on it. A
+// process blocked in the filesystem may not die until the request completes.
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug)]
+pub struct Test {
+    pub name: String,
+    pub ignored: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunOutcome {
+    Ok,
+    Failed,
+    Timeout,
+}
+
+impl RunOutcome {
+    /// The libtest JSON event name for this outcome.
+    pub fn event(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Failed => "failed",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TestRun {
+    pub outcome: RunOutcome,
+    /// Everything the test wrote to stdout and stderr.
+    pub output: String,
+    pub exec_time: Duration,
+}
+
+pub struct Runner {
+    /// The library test binary.
+    pub bin: PathBuf,
+    /// The run directory shared by all tests, relative to the test root.
+    pub run_dir: PathBuf,
+}
+
+impl Runner {
+    /// Build the tests of the crate in `manifest_dir` and prepare a run.
+    pub fn new(manifest_dir: &Path) -> Result<Self> {
+        let run_dir = match &config::config().run_dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(crate::test_path::rand_dir()),
+        };
+
+        Ok(Self {
+            bin: test_binary(manifest_dir)?,
+            run_dir,
+        })
+    }
+
+    /// List the tests matching `filter`, in the order libtest runs them.
+    pub fn list(&self, filter: Option<&str>) -> Result<Vec<Test>> {
+        let ignored = list_names(&self.bin, filter, true)?;
+        let tests = list_names(&self.bin, filter, false)?
+            .into_iter()
+            .map(|name| Test {
+                ignored: ignored.contains(&name),
+                name,
+            })
+            .collect();
+        Ok(tests)
+    }
+
+    /// Run the single test `name`, killing it after `timeout`. A `data_size`
+    /// overrides the configured size for the data tests.
+    pub fn run(
+        &self,
+        name: &str,
+        timeout: Duration,
+        data_size: Option<usize>,
+    ) -> Result<TestRun> {
+        let start = Instant::now();
+        let mut cmd = Command::new(&self.bin);
+        cmd.args([name, "--exact", "--test-threads", "1"])
+            .env("FSTESTING_RUN_DIR", &self.run_dir);
+        if let Some(size) = data_size {
+            cmd.env("FSTESTING_DATA_SIZE", size.to_string());
+        }
+
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = capture(child.stdout.take());
+        let stderr = capture(child.stderr.take());
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+
+            if start.elapsed() >= timeout {
+                break None;
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        };
+
+        let exec_time = start.elapsed();
+
+        let (outcome, exited) = match status {
+            Some(status) if status.success() => (RunOutcome::Ok, true),
+            Some(_) => (RunOutcome::Failed, true),
+            None => (RunOutcome::Timeout, kill(&mut child)?),
+        };
+
+        // The output pipes only close once the test has exited, so the
+        // output of a test that couldn't be killed is lost.
+        let mut output = String::new();
+        if exited {
+            output.push_str(&stdout.join().unwrap_or_default());
+            output.push_str(&stderr.join().unwrap_or_default());
+        }
+
+        if outcome == RunOutcome::Timeout {
+            output.push_str(&format!(
+                "fstesting: timed out after {}s\n",
+                timeout.as_secs()
+            ));
+        }
+
+        Ok(TestRun {
+            outcome,
+            output,
+            exec_time,
+        })
+    }
+}
+
+fn test_binary(manifest_dir: &Path) -> Result<PathBuf> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .current_dir(manifest_dir)
+        .args(["test", "--lib", "--no-run"])
+        .arg("--message-format=json-render-diagnostics")
+        .stderr(Stdio::inherit())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::other("Error building the test binary"));
+    }
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+
+        if msg["reason"] != "compiler-artifact"
+            || msg["profile"]["test"] != true
+        {
+            continue;
+        }
+
+        if let Some(exe) = msg["executable"].as_str() {
+            return Ok(PathBuf::from(exe));
+        }
+    }
+
+    Err(Error::other("Error locating the test binary"))
+}
+
+fn list_names(
+    bin: &Path,
+    filter: Option<&str>,
+    ignored: bool,
+) -> Result<Vec<String>> {
+    let mut cmd = Command::new(bin);
+    cmd.args(["--list", "--format", "terse"]);
+    if ignored {
+        cmd.arg("--ignored");
+    }
+    cmd.args(filter);
+
+    let output = cmd.stderr(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "Error listing tests from {}",
+            bin.display()
+        )));
+    }
+
+    let names = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(|name| name.to_owned())
+        .collect();
+    Ok(names)
+}
+
+/// Whether `name` is one of the tests that use `data_size`.
+pub fn is_data_test(name: &str) -> bool {
+    name.split("::").any(|m| DATA_MODULES.contains(&m))
+}
+
+/// The timeout configured for the test `name`.
+pub fn timeout_for(name: &str) -> Duration {
+    let config = config::config();
+    if is_data_test(name) {
+        config.data_timeout
+    } else {
+        config.timeout
+    }
+}
+
+// Kill the test and return whether it exited within `KILL_GRACE`.
+fn kill(child: &mut Child) -> Result<bool> {
+    child.kill()?;
+
+    let start = Instant::now();
+    while start.elapsed() < KILL_GRACE {
+        if child.try_wait()?.is_some() {
+            return Ok(true);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    Ok(false)
+}
+
+fn capture(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
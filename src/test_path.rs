@@ -1,7 +1,10 @@
+use std::ops::{Deref, DerefMut};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path;
 use std::sync;
 
-const TEST_ROOT: &str = "./mountpoint";
+use crate::config::{self, Keep};
+
 static TEST_PATH: sync::OnceLock<path::PathBuf> = sync::OnceLock::new();
 
 #[derive(Clone)]
@@ -29,6 +32,12 @@ impl TestPath {
     }
 }
 
+impl AsRef<path::Path> for TestPath {
+    fn as_ref(&self) -> &path::Path {
+        &self.path
+    }
+}
+
 impl From<path::PathBuf> for TestPath {
     fn from(path: path::PathBuf) -> Self {
         Self {
@@ -38,22 +47,152 @@ impl From<path::PathBuf> for TestPath {
     }
 }
 
-pub fn test_dir() -> TestPath {
+/// A per-test directory that is removed when the test finishes.
+///
+/// The directory is kept if the test panics so that the state of a failing
+/// test can be inspected. This is controlled by the `keep` config setting.
+pub struct TestDir {
+    dir: path::PathBuf,
+    path: TestPath,
+}
+
+impl Deref for TestDir {
+    type Target = TestPath;
+
+    fn deref(&self) -> &Self::Target {
+        &self.path
+    }
+}
+
+impl DerefMut for TestDir {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let failed = std::thread::panicking();
+        let keep = match config::config().keep {
+            Keep::Never => false,
+            Keep::Failed => failed,
+            Keep::Always => true,
+        };
+
+        if keep {
+            if failed {
+                eprintln!("Preserved test directory: {}", self.dir.display());
+            }
+            return;
+        }
+
+        if let Err(e) = remove_tree(&self.dir) {
+            eprintln!(
+                "Error removing test directory {}: {e}",
+                self.dir.display()
+            );
+            return;
+        }
+
+        // The run directory goes with the last test directory in it. This
+        // fails while other tests are still using it, or kept theirs.
+        if let Some(run_dir) = self.dir.parent() {
+            let _ = std::fs::remove_dir(run_dir);
+        }
+    }
+}
+
+pub fn test_dir() -> TestDir {
+    try_test_dir().expect("Error creating test directory.")
+}
+
+/// Like `test_dir`, but returns an error if the directory can't be created.
+pub fn try_test_dir() -> std::io::Result<TestDir> {
     let mut path = TEST_PATH
         .get_or_init(|| {
-            let mut p = path::PathBuf::from(TEST_ROOT);
-            p.push(rand_dir());
-            p
+            let root = test_root();
+            match &config::config().run_dir {
+                Some(dir) => root.join(dir),
+                None => root.join(rand_dir()),
+            }
         })
         .clone();
 
     path.push(rand_dir());
-    std::fs::create_dir_all(&path).expect("Error creating test directory.");
 
-    TestPath::from(path)
+    // A test that finishes concurrently can remove the run directory between
+    // creating it and creating this one inside it, so try again.
+    loop {
+        match std::fs::create_dir_all(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            res => break res?,
+        }
+    }
+
+    Ok(TestDir {
+        dir: path.clone(),
+        path: TestPath::from(path),
+    })
+}
+
+pub fn test_root() -> path::PathBuf {
+    let root = &config::config().root;
+
+    let meta = match std::fs::metadata(root) {
+        Ok(meta) => meta,
+        Err(e) => panic!(
+            "Test root {} is not accessible: {e}. Mount the filesystem under \
+             test there or set FSTESTING_ROOT.",
+            root.display()
+        ),
+    };
+
+    if !meta.is_dir() {
+        panic!("Test root {} is not a directory.", root.display());
+    }
+
+    if config::config().require_mount {
+        let parent = root.join("..");
+        let parent_meta = std::fs::metadata(&parent).unwrap_or_else(|e| {
+            panic!("Error reading parent of {}: {e}", root.display())
+        });
+
+        if meta.dev() == parent_meta.dev() {
+            panic!(
+                "Test root {} is not a mount point. Mount the filesystem \
+                 under test there or set FSTESTING_REQUIRE_MOUNT=false.",
+                root.display()
+            );
+        }
+    }
+
+    root.clone()
+}
+
+// Tests leave behind directories without read or execute permissions so
+// unlike `std::fs::remove_dir_all` this restores access before descending.
+fn remove_tree(path: &path::Path) -> std::io::Result<()> {
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    if !meta.is_dir() {
+        return std::fs::remove_file(path);
+    }
+
+    let perms = std::fs::Permissions::from_mode(0o700);
+    let _ = std::fs::set_permissions(path, perms);
+
+    for entry in std::fs::read_dir(path)? {
+        remove_tree(&entry?.path())?;
+    }
+
+    std::fs::remove_dir(path)
 }
 
-fn rand_dir() -> String {
+pub(crate) fn rand_dir() -> String {
     use rand::prelude::*;
 
     let mut rng = rand::rng();
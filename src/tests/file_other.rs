@@ -385,7 +385,7 @@ fn seek_04() {
     let st = crate::stat(&mut path);
     let stfs = crate::statfs(&mut path);
     assert_eq!(st.st_size, 1024 * 1024);
-    assert!(st.st_blocks * (stfs.f_bsize as i64) < (1024 * 1024));
+    assert!((st.st_blocks as u64) * (stfs.f_bsize as u64) < (1024 * 1024));
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
@@ -401,12 +401,17 @@ fn falloc_01() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR | libc::O_CREAT) };
     assert!(fd > 0);
 
-    let err = unsafe { libc::posix_fallocate(fd, 0, 1024 * 1024) };
+    // Call fallocate directly, as glibc's posix_fallocate falls back to
+    // writing zeroes when the filesystem doesn't support it.
+    let err = unsafe { libc::fallocate(fd, 0, 0, 1024 * 1024) };
+    if err != 0 {
+        crate::check_supported("fallocate", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let st = crate::stat(&mut path);
     let stfs = crate::statfs(&mut path);
-    assert!(st.st_blocks * (stfs.f_bsize as i64) >= (1024 * 1024));
+    assert!((st.st_blocks as u64) * (stfs.f_bsize as u64) >= (1024 * 1024));
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
@@ -427,12 +432,17 @@ fn falloc_02() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR | libc::O_CREAT) };
     assert!(fd > 0);
 
-    let err = unsafe { libc::posix_fallocate(fd, 12, 512) };
+    // Call fallocate directly, as glibc's posix_fallocate falls back to
+    // writing zeroes when the filesystem doesn't support it.
+    let err = unsafe { libc::fallocate(fd, 0, 12, 512) };
+    if err != 0 {
+        crate::check_supported("fallocate", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let st = crate::stat(&mut path);
     let stfs = crate::statfs(&mut path);
-    assert!(st.st_blocks * (stfs.f_bsize as i64) >= 525);
+    assert!((st.st_blocks as u64) * (stfs.f_bsize as u64) >= 525);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
@@ -567,6 +577,9 @@ fn flock_01() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_SH) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_UN) };
@@ -592,6 +605,9 @@ fn flock_02() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_EX) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_UN) };
@@ -617,6 +633,9 @@ fn flock_03() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_EX) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let mut thr_path = path.clone();
@@ -654,6 +673,9 @@ fn flock_04() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_SH) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let mut thr_path = path.clone();
@@ -694,6 +716,9 @@ fn flock_05() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_EX) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let mut thr_path = path.clone();
@@ -734,6 +759,9 @@ fn flock_06() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_SH) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_EX) };
@@ -762,6 +790,9 @@ fn flock_07() {
     assert!(fd > 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_EX) };
+    if err == -1 {
+        crate::check_supported("flock", crate::errno());
+    }
     assert_eq!(err, 0);
 
     let err = unsafe { libc::flock(fd, libc::LOCK_SH) };
@@ -940,6 +971,7 @@ fn fadvise_01() {
 
     let err =
         unsafe { libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_NORMAL) };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -962,6 +994,7 @@ fn fadvise_02() {
     let err = unsafe {
         libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_SEQUENTIAL)
     };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -983,6 +1016,7 @@ fn fadvise_03() {
 
     let err =
         unsafe { libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_RANDOM) };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -1004,6 +1038,7 @@ fn fadvise_04() {
 
     let err =
         unsafe { libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_NOREUSE) };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -1025,6 +1060,7 @@ fn fadvise_05() {
 
     let err =
         unsafe { libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_WILLNEED) };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -1046,6 +1082,7 @@ fn fadvise_06() {
 
     let err =
         unsafe { libc::posix_fadvise(fd, 0, 1024, libc::POSIX_FADV_WILLNEED) };
+    crate::check_supported("posix_fadvise", err);
     assert_eq!(err, 0);
 
     let err = unsafe { libc::close(fd) };
@@ -1074,6 +1111,9 @@ fn cprange_01() {
     let len = unsafe {
         libc::copy_file_range(fd, &mut src_offset, fd, &mut dst_offset, 1024, 0)
     };
+    if len == -1 {
+        crate::check_supported("copy_file_range", crate::errno());
+    }
     assert_eq!(len, 1024);
 
     let err = unsafe { libc::close(fd) };
@@ -1116,6 +1156,9 @@ fn cprange_02() {
             0,
         )
     };
+    if len == -1 {
+        crate::check_supported("copy_file_range", crate::errno());
+    }
     assert_eq!(len, 2048);
 
     let err = unsafe { libc::close(src_fd) };
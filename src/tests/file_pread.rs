@@ -1,6 +1,6 @@
 use rand::prelude::*;
 
-use super::DATA_SIZE;
+use super::data_size;
 
 /// pread_01: Read a file 13 bytes at a time and check EOF behavior
 #[test]
@@ -8,7 +8,7 @@ fn pread_01() {
     let mut path = crate::test_dir();
     path.push("pread_01.txt");
 
-    let mut data = vec![0u8; DATA_SIZE];
+    let mut data = vec![0u8; data_size()];
     for (i, val) in data.iter_mut().enumerate() {
         let char: u8 = 97 + (i % 26) as u8;
         *val = char;
@@ -19,7 +19,7 @@ fn pread_01() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
     assert!(fd > 0);
 
-    for idx in (0..DATA_SIZE).step_by(26) {
+    for idx in (0..data_size()).step_by(26) {
         let mut bytes = vec![0u8; 13];
         let len = unsafe {
             libc::pread(
@@ -29,14 +29,14 @@ fn pread_01() {
                 idx as i64,
             )
         };
-        let should_read = std::cmp::min(13, DATA_SIZE - idx);
+        let should_read = std::cmp::min(13, data_size() - idx);
         assert_eq!(len, should_read as isize);
         assert_eq!(
             &bytes[..should_read],
-            "abcdefghijklm"[..should_read].as_bytes()
+            &"abcdefghijklm".as_bytes()[..should_read]
         );
 
-        if (idx + 13) > DATA_SIZE {
+        if (idx + 13) > data_size() {
             break;
         }
 
@@ -48,11 +48,11 @@ fn pread_01() {
                 (idx + 13) as i64,
             )
         };
-        let should_read = std::cmp::min(13, DATA_SIZE - (idx + 13));
+        let should_read = std::cmp::min(13, data_size() - (idx + 13));
         assert_eq!(len, should_read as isize);
         assert_eq!(
             &bytes[..should_read],
-            "nopqrstuvwxyz"[..should_read].as_bytes()
+            &"nopqrstuvwxyz".as_bytes()[..should_read]
         );
     }
 
@@ -69,7 +69,7 @@ fn pread_02() {
     let mut path = crate::test_dir();
     path.push("pread_02.txt");
 
-    let mut data = vec![0u8; DATA_SIZE];
+    let mut data = vec![0u8; data_size()];
     for (i, val) in data.iter_mut().enumerate() {
         let char: u8 = 97 + (i % 26) as u8;
         *val = char;
@@ -82,7 +82,7 @@ fn pread_02() {
 
     let t1 = std::thread::spawn(move || {
         let mut bytes = vec![0u8; 13];
-        for idx in (0..DATA_SIZE).step_by(26) {
+        for idx in (0..data_size()).step_by(26) {
             let len = unsafe {
                 libc::pread(
                     fd,
@@ -91,18 +91,18 @@ fn pread_02() {
                     idx as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - idx);
+            let should_read = std::cmp::min(13, data_size() - idx);
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "abcdefghijklm"[..should_read].as_bytes()
+                &"abcdefghijklm".as_bytes()[..should_read]
             );
         }
     });
 
     let t2 = std::thread::spawn(move || {
         let mut bytes = vec![0u8; 13];
-        for idx in (13..DATA_SIZE).step_by(26) {
+        for idx in (13..data_size()).step_by(26) {
             let len = unsafe {
                 libc::pread(
                     fd,
@@ -111,11 +111,11 @@ fn pread_02() {
                     idx as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - idx);
+            let should_read = std::cmp::min(13, data_size() - idx);
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "nopqrstuvwxyz"[..should_read].as_bytes()
+                &"nopqrstuvwxyz".as_bytes()[..should_read]
             );
         }
     });
@@ -134,7 +134,7 @@ fn pread_03() {
     let mut path = crate::test_dir();
     path.push("pread_03.txt");
 
-    let mut data = vec![0u8; DATA_SIZE];
+    let mut data = vec![0u8; data_size()];
     for (i, val) in data.iter_mut().enumerate() {
         let char: u8 = 97 + (i % 26) as u8;
         *val = char;
@@ -149,7 +149,7 @@ fn pread_03() {
         let mut rng = rand::rng();
         let mut bytes = vec![0u8; 13];
         for _ in 0..2048 {
-            let pos = rng.random_range(0..=(DATA_SIZE / 26));
+            let pos = rng.random_range(0..=(data_size() / 26));
             let len = unsafe {
                 libc::pread(
                     fd,
@@ -158,11 +158,11 @@ fn pread_03() {
                     (pos * 26) as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - (pos * 26));
+            let should_read = std::cmp::min(13, data_size() - (pos * 26));
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "abcdefghijklm"[..should_read].as_bytes()
+                &"abcdefghijklm".as_bytes()[..should_read]
             );
         }
     });
@@ -171,20 +171,24 @@ fn pread_03() {
         let mut rng = rand::rng();
         let mut bytes = vec![0u8; 13];
         for _ in 0..2048 {
-            let pos = rng.random_range(0..(DATA_SIZE / 26));
+            // Files shorter than 13 bytes past the last multiple of 26 read
+            // nothing there, as do files shorter than 26 bytes.
+            let pos = rng.random_range(0..=(data_size() / 26));
+            let offset = (pos * 26) + 13;
             let len = unsafe {
                 libc::pread(
                     fd,
                     bytes.as_mut_ptr() as *mut libc::c_void,
                     bytes.len(),
-                    ((pos * 26) + 13) as i64,
+                    offset as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - ((pos * 26) + 13));
+            let should_read =
+                std::cmp::min(13, data_size().saturating_sub(offset));
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "nopqrstuvwxyz"[..should_read].as_bytes()
+                &"nopqrstuvwxyz".as_bytes()[..should_read]
             );
         }
     });
@@ -203,7 +207,7 @@ fn pread_04() {
     let mut path = crate::test_dir();
     path.push("pread_04.txt");
 
-    let mut data = vec![0u8; DATA_SIZE];
+    let mut data = vec![0u8; data_size()];
     for (i, val) in data.iter_mut().enumerate() {
         let char: u8 = 97 + (i % 26) as u8;
         *val = char;
@@ -219,7 +223,7 @@ fn pread_04() {
         let mut rng = rand::rng();
         let mut bytes = vec![0u8; 13];
         for _ in 0..2048 {
-            let pos = rng.random_range(0..=(DATA_SIZE / 26));
+            let pos = rng.random_range(0..=(data_size() / 26));
             let len = unsafe {
                 libc::pread(
                     fd,
@@ -228,11 +232,11 @@ fn pread_04() {
                     (pos * 26) as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - (pos * 26));
+            let should_read = std::cmp::min(13, data_size() - (pos * 26));
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "abcdefghijklm"[..should_read].as_bytes()
+                &"abcdefghijklm".as_bytes()[..should_read]
             );
         }
 
@@ -248,20 +252,24 @@ fn pread_04() {
         let mut rng = rand::rng();
         let mut bytes = vec![0u8; 13];
         for _ in 0..2048 {
-            let pos = rng.random_range(0..(DATA_SIZE / 26));
+            // Files shorter than 13 bytes past the last multiple of 26 read
+            // nothing there, as do files shorter than 26 bytes.
+            let pos = rng.random_range(0..=(data_size() / 26));
+            let offset = (pos * 26) + 13;
             let len = unsafe {
                 libc::pread(
                     fd,
                     bytes.as_mut_ptr() as *mut libc::c_void,
                     bytes.len(),
-                    ((pos * 26) + 13) as i64,
+                    offset as i64,
                 )
             };
-            let should_read = std::cmp::min(13, DATA_SIZE - ((pos * 26) + 13));
+            let should_read =
+                std::cmp::min(13, data_size().saturating_sub(offset));
             assert_eq!(len, should_read as isize);
             assert_eq!(
                 &bytes[..should_read],
-                "nopqrstuvwxyz"[..should_read].as_bytes()
+                &"nopqrstuvwxyz".as_bytes()[..should_read]
             );
         }
 
@@ -2,7 +2,7 @@ use std::sync::mpsc;
 
 use rand::seq::SliceRandom;
 
-use super::DATA_SIZE;
+use super::data_size;
 use crate::file_size;
 
 /// pwrite_01: Write file linearly
@@ -22,7 +22,7 @@ fn pwrite_01() {
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
 
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -34,13 +34,13 @@ fn pwrite_01() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -51,7 +51,7 @@ fn pwrite_01() {
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
-    assert_eq!(file_size(&mut path), DATA_SIZE);
+    assert_eq!(file_size(&mut path), data_size());
 }
 
 /// pwrite_02: Create empty file, open and write linearly
@@ -76,7 +76,7 @@ fn pwrite_02() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -88,13 +88,13 @@ fn pwrite_02() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -106,7 +106,7 @@ fn pwrite_02() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_03: Write file, rewrite first half
@@ -125,7 +125,7 @@ fn pwrite_03() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -137,13 +137,13 @@ fn pwrite_03() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -151,7 +151,7 @@ fn pwrite_03() {
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
     assert_eq!(offset, 0);
 
-    for idx in 0..((DATA_SIZE / 26) / 2) {
+    for idx in 0..((data_size() / 26) / 2) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -170,7 +170,7 @@ fn pwrite_03() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_04: Write half, close, open, rewrite second half.
@@ -189,7 +189,7 @@ fn pwrite_04() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -201,13 +201,13 @@ fn pwrite_04() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -221,7 +221,7 @@ fn pwrite_04() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
     assert!(fd > 0);
 
-    for idx in ((DATA_SIZE / 26) / 2)..(DATA_SIZE / 26) {
+    for idx in ((data_size() / 26) / 2)..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -240,7 +240,7 @@ fn pwrite_04() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_05: Write half, reopen, write second half
@@ -259,7 +259,7 @@ fn pwrite_05() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for idx in 0..((DATA_SIZE / 26) / 2) {
+    for idx in 0..((data_size() / 26) / 2) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -277,7 +277,7 @@ fn pwrite_05() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
     assert!(fd > 0);
 
-    for idx in ((DATA_SIZE / 26) / 2)..(DATA_SIZE / 26) {
+    for idx in ((data_size() / 26) / 2)..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -289,13 +289,13 @@ fn pwrite_05() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -307,7 +307,7 @@ fn pwrite_05() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_06: Interleaved writes
@@ -326,7 +326,7 @@ fn pwrite_06() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklm";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -342,7 +342,7 @@ fn pwrite_06() {
     assert_eq!(offset, 0);
 
     let bytes = "nopqrstuvwxyz";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -355,13 +355,13 @@ fn pwrite_06() {
     }
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -373,7 +373,7 @@ fn pwrite_06() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_07: Write file backwards
@@ -392,18 +392,18 @@ fn pwrite_07() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
 
-    for idx in (0..(DATA_SIZE / 26)).rev() {
+    for idx in (0..(data_size() / 26)).rev() {
         let len = unsafe {
             libc::pwrite(
                 fd,
@@ -422,7 +422,7 @@ fn pwrite_07() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_08: Scattered writes
@@ -432,7 +432,7 @@ fn pwrite_08() {
     path.push("pwrite_08.txt");
 
     let mut rng = rand::rng();
-    let mut positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     positions.shuffle(&mut rng);
 
     let fd = unsafe {
@@ -458,13 +458,13 @@ fn pwrite_08() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -475,7 +475,7 @@ fn pwrite_08() {
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
-    assert_eq!(file_size(&mut path), DATA_SIZE);
+    assert_eq!(file_size(&mut path), data_size());
 }
 
 /// pwrite_09: Parallel writes to same fd
@@ -495,7 +495,7 @@ fn pwrite_09() {
 
     let t1 = std::thread::spawn(move || {
         let bytes = "abcdefghijklm";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -510,7 +510,7 @@ fn pwrite_09() {
 
     let t2 = std::thread::spawn(move || {
         let bytes = "nopqrstuvwxyz";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -527,13 +527,13 @@ fn pwrite_09() {
     t2.join().unwrap();
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -545,7 +545,7 @@ fn pwrite_09() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_10: Parallel writes to separate fds
@@ -566,7 +566,7 @@ fn pwrite_10() {
         assert!(fd > 0);
 
         let bytes = "abcdefghijklm";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -594,7 +594,7 @@ fn pwrite_10() {
         assert!(fd > 0);
 
         let bytes = "nopqrstuvwxyz";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -623,13 +623,13 @@ fn pwrite_10() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -641,7 +641,7 @@ fn pwrite_10() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_11: parallel scattered writes same fd
@@ -652,10 +652,10 @@ fn pwrite_11() {
 
     let mut rng = rand::rng();
 
-    let mut t1_positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut t1_positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     t1_positions.shuffle(&mut rng);
 
-    let mut t2_positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut t2_positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     t2_positions.shuffle(&mut rng);
 
     let fd = unsafe {
@@ -701,13 +701,13 @@ fn pwrite_11() {
     t2.join().unwrap();
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -719,7 +719,7 @@ fn pwrite_11() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_12: parallel scattered writes separate fds
@@ -730,10 +730,10 @@ fn pwrite_12() {
 
     let mut rng = rand::rng();
 
-    let mut t1_positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut t1_positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     t1_positions.shuffle(&mut rng);
 
-    let mut t2_positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut t2_positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     t2_positions.shuffle(&mut rng);
 
     let mut p1 = path.clone();
@@ -805,13 +805,13 @@ fn pwrite_12() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -823,7 +823,7 @@ fn pwrite_12() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_13: Parallel overlapping writes with same contents
@@ -846,7 +846,7 @@ fn pwrite_13() {
 
     let t1 = std::thread::spawn(move || {
         let bytes = "abcdefghijklmnopqrstuvwxyz";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -867,7 +867,7 @@ fn pwrite_13() {
 
     let t2 = std::thread::spawn(move || {
         let bytes = "abcdefghijklmnopqrstuvwxyz";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -890,13 +890,13 @@ fn pwrite_13() {
     t2.join().unwrap();
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -908,7 +908,7 @@ fn pwrite_13() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_14: Parallel overlapping writes with different contents
@@ -931,7 +931,7 @@ fn pwrite_14() {
 
     let t1 = std::thread::spawn(move || {
         let bytes = "abcdefghijklmnopqrstuvwxyz";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -952,7 +952,7 @@ fn pwrite_14() {
 
     let t2 = std::thread::spawn(move || {
         let bytes = "ZYXWVUTSRQPONMLKJIHGFEDCBA";
-        for idx in 0..(DATA_SIZE / 26) {
+        for idx in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::pwrite(
                     fd,
@@ -975,13 +975,13 @@ fn pwrite_14() {
     t2.join().unwrap();
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::pwrite(
             fd,
             bytes.as_bytes()[..tail].as_ptr() as *const libc::c_void,
             tail,
-            (DATA_SIZE / 26 * 26) as i64,
+            (data_size() / 26 * 26) as i64,
         )
     };
     assert_eq!(len, tail as isize);
@@ -993,7 +993,7 @@ fn pwrite_14() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// pwrite_15: pwrite *is* affected by O_APPEND
@@ -1,4 +1,4 @@
-use super::DATA_SIZE;
+use super::data_size;
 
 /// read_01: Read a file 13 bytes at a time and check correct EOF behavior.
 #[test]
@@ -6,7 +6,7 @@ fn read_01() {
     let mut path = crate::test_dir();
     path.push("read_01.txt");
 
-    let mut data = vec![0u8; DATA_SIZE];
+    let mut data = vec![0u8; data_size()];
     for (i, val) in data.iter_mut().enumerate() {
         let char: u8 = 97 + (i % 26) as u8;
         *val = char;
@@ -17,7 +17,7 @@ fn read_01() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
     assert!(fd > 0);
 
-    for _ in 0..(DATA_SIZE / 26) {
+    for _ in 0..(data_size() / 26) {
         let mut bytes = vec![0u8; 13];
         let len = unsafe {
             libc::read(fd, bytes.as_mut_ptr() as *mut libc::c_void, bytes.len())
@@ -32,12 +32,12 @@ fn read_01() {
         assert_eq!(bytes, "nopqrstuvwxyz".as_bytes());
     }
 
-    let mut bytes = vec![0u8; DATA_SIZE % 26];
+    let mut bytes = vec![0u8; data_size() % 26];
     let len = unsafe {
         libc::read(fd, bytes.as_mut_ptr() as *mut libc::c_void, bytes.len())
     };
-    assert_eq!(len, (DATA_SIZE % 26) as isize);
-    assert_eq!(bytes, "abcdefghijklmnopqrstuvwxyz"[..(DATA_SIZE % 26)].as_bytes());
+    assert_eq!(len, (data_size() % 26) as isize);
+    assert_eq!(bytes, &"abcdefghijklmnopqrstuvwxyz".as_bytes()[..(data_size() % 26)]);
 
     let len = unsafe {
         libc::read(fd, bytes.as_mut_ptr() as *mut libc::c_void, bytes.len())
@@ -1,6 +1,6 @@
 use rand::prelude::*;
 
-use super::DATA_SIZE;
+use super::data_size;
 use crate::file_size;
 
 /// write_01: Write file linearly
@@ -20,7 +20,7 @@ fn write_01() {
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
 
-    for _ in 0..(DATA_SIZE / 26) {
+    for _ in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -31,7 +31,7 @@ fn write_01() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -42,12 +42,12 @@ fn write_01() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
-    assert_eq!(file_size(&mut path), DATA_SIZE);
+    assert_eq!(file_size(&mut path), data_size());
 }
 
 /// write_02: Create empty file, open and write linearly
@@ -81,7 +81,7 @@ fn write_02() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for _ in 0..(DATA_SIZE / 26) {
+    for _ in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -92,7 +92,7 @@ fn write_02() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -103,13 +103,14 @@ fn write_02() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
+    // The initial "foo" is only fully overwritten by at least 3 bytes.
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size().max(3));
 }
 
 /// write_03: Write file, rewrite first half
@@ -128,7 +129,7 @@ fn write_03() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for _ in 0..(DATA_SIZE / 26) {
+    for _ in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -139,7 +140,7 @@ fn write_03() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -150,12 +151,12 @@ fn write_03() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_SET) };
     assert_eq!(offset, 0);
 
-    for _ in 0..((DATA_SIZE / 26) / 2) {
+    for _ in 0..((data_size() / 26) / 2) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -170,7 +171,7 @@ fn write_03() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_04: Write half, close, open, rewrite second half.
@@ -189,7 +190,7 @@ fn write_04() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for _ in 0..(DATA_SIZE / 26) {
+    for _ in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -200,7 +201,7 @@ fn write_04() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -211,7 +212,7 @@ fn write_04() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
@@ -219,7 +220,7 @@ fn write_04() {
     let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
     assert!(fd > 0);
 
-    for _ in ((DATA_SIZE / 26) / 2)..(DATA_SIZE / 26) {
+    for _ in ((data_size() / 26) / 2)..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -234,7 +235,7 @@ fn write_04() {
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_05: Write half, reopen, write second half
@@ -253,7 +254,7 @@ fn write_05() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    for _ in 0..((DATA_SIZE / 26) / 2) {
+    for _ in 0..((data_size() / 26) / 2) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -275,7 +276,7 @@ fn write_05() {
     let offset = unsafe { libc::lseek(fd, written, libc::SEEK_SET) };
     assert_eq!(offset, written);
 
-    for _ in ((DATA_SIZE / 26) / 2)..(DATA_SIZE / 26) {
+    for _ in ((data_size() / 26) / 2)..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -286,7 +287,7 @@ fn write_05() {
         assert_eq!(len, bytes.len() as isize);
     }
 
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -297,13 +298,13 @@ fn write_05() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_06: Interleaved writes
@@ -322,7 +323,7 @@ fn write_06() {
     assert!(fd > 0);
 
     let bytes = "abcdefghijklm";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -340,7 +341,7 @@ fn write_06() {
     assert_eq!(offset, 13);
 
     let bytes = "nopqrstuvwxyz";
-    for idx in 0..(DATA_SIZE / 26) {
+    for idx in 0..(data_size() / 26) {
         let len = unsafe {
             libc::write(
                 fd,
@@ -356,12 +357,12 @@ fn write_06() {
 
     // Undo the last lseek of the previous loop
     let offset = unsafe {
-        libc::lseek(fd, (DATA_SIZE / 26 * 26) as i64, libc::SEEK_SET)
+        libc::lseek(fd, (data_size() / 26 * 26) as i64, libc::SEEK_SET)
     };
-    assert_eq!(offset, (DATA_SIZE / 26 * 26) as i64);
+    assert_eq!(offset, (data_size() / 26 * 26) as i64);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -372,13 +373,13 @@ fn write_06() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_07: Write file backwards
@@ -397,12 +398,12 @@ fn write_07() {
     assert!(fd > 0);
 
     let offset = unsafe {
-        libc::lseek(fd, (DATA_SIZE / 26 * 26) as i64, libc::SEEK_SET)
+        libc::lseek(fd, (data_size() / 26 * 26) as i64, libc::SEEK_SET)
     };
-    assert_eq!(offset, (DATA_SIZE / 26 * 26) as i64);
+    assert_eq!(offset, (data_size() / 26 * 26) as i64);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -412,7 +413,7 @@ fn write_07() {
     };
     assert_eq!(len, tail as isize);
 
-    for idx in (0..(DATA_SIZE / 26)).rev() {
+    for idx in (0..(data_size() / 26)).rev() {
         let offset =
             unsafe { libc::lseek(fd, (idx * 26) as i64, libc::SEEK_SET) };
         assert_eq!(offset, (idx * 26) as i64);
@@ -428,13 +429,13 @@ fn write_07() {
     }
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, 26);
+    assert_eq!(offset, data_size().min(26) as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_08: write scattered positions
@@ -444,7 +445,7 @@ fn write_08() {
     path.push("write_08.txt");
 
     let mut rng = rand::rng();
-    let mut positions = (0..(DATA_SIZE / 26)).collect::<Vec<_>>();
+    let mut positions = (0..(data_size() / 26)).collect::<Vec<_>>();
     positions.shuffle(&mut rng);
 
     let fd = unsafe {
@@ -473,12 +474,12 @@ fn write_08() {
     }
 
     let offset = unsafe {
-        libc::lseek(fd, (DATA_SIZE / 26 * 26) as i64, libc::SEEK_SET)
+        libc::lseek(fd, (data_size() / 26 * 26) as i64, libc::SEEK_SET)
     };
-    assert_eq!(offset, (DATA_SIZE / 26 * 26) as i64);
+    assert_eq!(offset, (data_size() / 26 * 26) as i64);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -489,13 +490,13 @@ fn write_08() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_09: Parallel writes to same fd
@@ -515,7 +516,7 @@ fn write_09() {
 
     let t1 = std::thread::spawn(move || {
         let bytes = "abcdefghijklm";
-        for _ in 0..(DATA_SIZE / 26) {
+        for _ in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::write(
                     fd,
@@ -529,7 +530,7 @@ fn write_09() {
 
     let t2 = std::thread::spawn(move || {
         let bytes = "nopqrstuvwxyz";
-        for _ in 0..(DATA_SIZE / 26) {
+        for _ in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::write(
                     fd,
@@ -545,7 +546,7 @@ fn write_09() {
     t2.join().unwrap();
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -556,13 +557,13 @@ fn write_09() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    assert_eq!(size, data_size());
 }
 
 /// write_10: Parallel writes to separate fds
@@ -583,7 +584,7 @@ fn write_10() {
         assert!(fd > 0);
 
         let bytes = "abcdefghijklm";
-        for _ in 0..(DATA_SIZE / 26) {
+        for _ in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::write(
                     fd,
@@ -610,7 +611,7 @@ fn write_10() {
         assert!(fd > 0);
 
         let bytes = "nopqrstuvwxyz";
-        for _ in 0..(DATA_SIZE / 26) {
+        for _ in 0..(data_size() / 26) {
             let len = unsafe {
                 libc::write(
                     fd,
@@ -638,12 +639,12 @@ fn write_10() {
     assert!(fd > 0);
 
     let offset = unsafe {
-        libc::lseek(fd, (DATA_SIZE / 26 * 26) as i64, libc::SEEK_SET)
+        libc::lseek(fd, (data_size() / 26 * 26) as i64, libc::SEEK_SET)
     };
-    assert_eq!(offset, (DATA_SIZE / 26 * 26) as i64);
+    assert_eq!(offset, (data_size() / 26 * 26) as i64);
 
     let bytes = "abcdefghijklmnopqrstuvwxyz";
-    let tail = DATA_SIZE % 26;
+    let tail = data_size() % 26;
     let len = unsafe {
         libc::write(
             fd,
@@ -654,13 +655,19 @@ fn write_10() {
     assert_eq!(len, tail as isize);
 
     let offset = unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) };
-    assert_eq!(offset, DATA_SIZE as i64);
+    assert_eq!(offset, data_size() as i64);
 
     let err = unsafe { libc::close(fd) };
     assert_eq!(err, 0);
 
+    // Both threads write the first half of the file from offset 0, so only a
+    // non-empty tail extends it to the full size.
     let size = file_size(&mut path);
-    assert_eq!(size, DATA_SIZE);
+    if tail == 0 {
+        assert_eq!(size, data_size() / 2);
+    } else {
+        assert_eq!(size, data_size());
+    }
 }
 
 /// write_11: Check append only with truncate and write
@@ -0,0 +1,503 @@
+// POSIX record locks are owned by the process, so conflicts are checked from
+// a forked child. The child must not allocate or panic, it only reports the
+// result of its calls through its exit status.
+
+/// lock_01: F_SETLK write lock a range and unlock it
+#[test]
+fn lock_01() {
+    let mut path = crate::test_dir();
+    path.push("lock_01.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 5);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_UNLCK, 0, 5);
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_02: F_GETLK reports F_UNLCK when nothing conflicts
+#[test]
+fn lock_02() {
+    let mut path = crate::test_dir();
+    path.push("lock_02.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let (err, lock) = get_lock(fd, libc::F_GETLK, libc::F_WRLCK, 0, 0);
+    if err == -1 {
+        crate::check_supported("fcntl(F_GETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+    assert_eq!(lock.l_type, libc::F_UNLCK as libc::c_short);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_03: A write lock conflicts with a write lock from another process
+#[test]
+fn lock_03() {
+    let mut path = crate::test_dir();
+    path.push("lock_03.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    // POSIX allows either EAGAIN or EACCES for a conflicting lock.
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 5, 10));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 9, 1));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_04: Read locks from different processes don't conflict
+#[test]
+fn lock_04() {
+    let mut path = crate::test_dir();
+    path.push("lock_04.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 0, 10));
+    assert_eq!(errno, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 5, 1));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_05: Locks on non-overlapping ranges don't conflict
+#[test]
+fn lock_05() {
+    let mut path = crate::test_dir();
+    path.push("lock_05.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    // Locks may extend past the end of the file.
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 10, 0));
+    assert_eq!(errno, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_06: F_GETLK from another process describes the conflicting lock
+#[test]
+fn lock_06() {
+    let mut path = crate::test_dir();
+    path.push("lock_06.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 2, 4);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let pid = unsafe { libc::getpid() };
+    let errno = in_child(|| {
+        let (err, lock) = get_lock(fd, libc::F_GETLK, libc::F_RDLCK, 0, 0);
+        let found = err == 0
+            && lock.l_type == libc::F_WRLCK as libc::c_short
+            && lock.l_start == 2
+            && lock.l_len == 4
+            && lock.l_pid == pid;
+        if found { 0 } else { -1 }
+    });
+    assert_eq!(errno, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_07: Unlocking the middle of a lock splits it in two
+#[test]
+fn lock_07() {
+    let mut path = crate::test_dir();
+    path.push("lock_07.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 30);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_UNLCK, 10, 10);
+    assert_eq!(err, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 10, 10));
+    assert_eq!(errno, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 9, 1));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 20, 1));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_08: Adjacent locks of the same type are merged
+#[test]
+fn lock_08() {
+    let mut path = crate::test_dir();
+    path.push("lock_08.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 10, 10);
+    assert_eq!(err, 0);
+
+    let errno = in_child(|| {
+        let (err, lock) = get_lock(fd, libc::F_GETLK, libc::F_WRLCK, 0, 0);
+        let merged = err == 0 && lock.l_start == 0 && lock.l_len == 20;
+        if merged { 0 } else { -1 }
+    });
+    assert_eq!(errno, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_09: Converting part of a write lock to a read lock
+#[test]
+fn lock_09() {
+    let mut path = crate::test_dir();
+    path.push("lock_09.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 20);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 10, 10);
+    assert_eq!(err, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 10, 10));
+    assert_eq!(errno, 0);
+
+    let errno = in_child(|| set_lock(fd, libc::F_SETLK, libc::F_RDLCK, 0, 10));
+    assert!(errno == libc::EAGAIN || errno == libc::EACCES);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_10: Locks of one process never conflict with each other
+#[test]
+fn lock_10() {
+    let mut path = crate::test_dir();
+    path.push("lock_10.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd1 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd1 > 0);
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd2 > 0);
+
+    let err = set_lock(fd1, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd2, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd1) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd2) };
+    assert_eq!(err, 0);
+}
+
+/// lock_11: Closing any fd of the file releases the process's locks
+#[test]
+fn lock_11() {
+    let mut path = crate::test_dir();
+    path.push("lock_11.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd1 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd1 > 0);
+
+    let err = set_lock(fd1, libc::F_SETLK, libc::F_WRLCK, 0, 0);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd2 > 0);
+
+    let err = unsafe { libc::close(fd2) };
+    assert_eq!(err, 0);
+
+    let errno = in_child(|| set_lock(fd1, libc::F_SETLK, libc::F_WRLCK, 0, 0));
+    assert_eq!(errno, 0);
+
+    let err = unsafe { libc::close(fd1) };
+    assert_eq!(err, 0);
+}
+
+/// lock_12: F_SETLKW waits for a conflicting lock to be released
+#[test]
+fn lock_12() {
+    let mut path = crate::test_dir();
+    path.push("lock_12.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = set_lock(fd, libc::F_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_supported("fcntl(F_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let pid = fork_child(|| set_lock(fd, libc::F_SETLKW, libc::F_WRLCK, 0, 10));
+
+    // Give the child time to block on the lock before releasing it.
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let err = set_lock(fd, libc::F_SETLK, libc::F_UNLCK, 0, 10);
+    assert_eq!(err, 0);
+
+    assert_eq!(wait_child(pid), 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// lock_13: OFD locks conflict between open file descriptions
+#[cfg(target_os = "linux")]
+#[test]
+fn lock_13() {
+    let mut path = crate::test_dir();
+    path.push("lock_13.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd1 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd1 > 0);
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd2 > 0);
+
+    let err = set_lock(fd1, libc::F_OFD_SETLK, libc::F_WRLCK, 0, 10);
+    if err == -1 {
+        crate::check_flag_supported("fcntl(F_OFD_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd2, libc::F_OFD_SETLK, libc::F_WRLCK, 5, 10);
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EAGAIN);
+
+    // A dup shares the open file description and its locks.
+    let fd3 = unsafe { libc::dup(fd1) };
+    assert!(fd3 > 0);
+
+    let err = set_lock(fd3, libc::F_OFD_SETLK, libc::F_WRLCK, 5, 10);
+    assert_eq!(err, 0);
+
+    for fd in [fd1, fd2, fd3] {
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+    }
+}
+
+/// lock_14: F_OFD_GETLK reports a conflicting OFD lock with a pid of -1
+#[cfg(target_os = "linux")]
+#[test]
+fn lock_14() {
+    let mut path = crate::test_dir();
+    path.push("lock_14.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd1 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd1 > 0);
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd2 > 0);
+
+    let err = set_lock(fd1, libc::F_OFD_SETLK, libc::F_RDLCK, 3, 7);
+    if err == -1 {
+        crate::check_flag_supported("fcntl(F_OFD_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let (err, lock) = get_lock(fd2, libc::F_OFD_GETLK, libc::F_WRLCK, 0, 0);
+    assert_eq!(err, 0);
+    assert_eq!(lock.l_type, libc::F_RDLCK as libc::c_short);
+    assert_eq!(lock.l_start, 3);
+    assert_eq!(lock.l_len, 7);
+    assert_eq!(lock.l_pid, -1);
+
+    for fd in [fd1, fd2] {
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+    }
+}
+
+/// lock_15: OFD locks are only released when their description is closed
+#[cfg(target_os = "linux")]
+#[test]
+fn lock_15() {
+    let mut path = crate::test_dir();
+    path.push("lock_15.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd1 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd1 > 0);
+
+    let err = set_lock(fd1, libc::F_OFD_SETLK, libc::F_WRLCK, 0, 0);
+    if err == -1 {
+        crate::check_flag_supported("fcntl(F_OFD_SETLK)", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd2 > 0);
+
+    let fd3 = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd3 > 0);
+
+    let err = unsafe { libc::close(fd3) };
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd2, libc::F_OFD_SETLK, libc::F_WRLCK, 0, 0);
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EAGAIN);
+
+    let err = unsafe { libc::close(fd1) };
+    assert_eq!(err, 0);
+
+    let err = set_lock(fd2, libc::F_OFD_SETLK, libc::F_WRLCK, 0, 0);
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd2) };
+    assert_eq!(err, 0);
+}
+
+fn new_lock(kind: i32, start: i64, len: i64) -> libc::flock {
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = kind as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    lock.l_start = start;
+    lock.l_len = len;
+    lock
+}
+
+fn set_lock(fd: i32, cmd: i32, kind: i32, start: i64, len: i64) -> i32 {
+    let mut lock = new_lock(kind, start, len);
+    unsafe {
+        crate::fcntl_ptr(fd, cmd, &mut lock as *mut _ as *mut libc::c_void)
+    }
+}
+
+fn get_lock(
+    fd: i32,
+    cmd: i32,
+    kind: i32,
+    start: i64,
+    len: i64,
+) -> (i32, libc::flock) {
+    let mut lock = new_lock(kind, start, len);
+    let err = unsafe {
+        crate::fcntl_ptr(fd, cmd, &mut lock as *mut _ as *mut libc::c_void)
+    };
+    (err, lock)
+}
+
+// Run `f` in a child process and return 0 if it returned 0, otherwise the
+// errno it left behind.
+fn in_child(f: impl FnOnce() -> i32) -> i32 {
+    wait_child(fork_child(f))
+}
+
+fn fork_child(f: impl FnOnce() -> i32) -> libc::pid_t {
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+
+    if pid == 0 {
+        let code = match f() {
+            0 => 0,
+            _ => crate::errno().clamp(1, 255),
+        };
+        unsafe { libc::_exit(code) };
+    }
+
+    pid
+}
+
+fn wait_child(pid: libc::pid_t) -> i32 {
+    let mut status = 0;
+    let ret = unsafe { libc::waitpid(pid, &mut status, 0) };
+    assert_eq!(ret, pid);
+    assert!(libc::WIFEXITED(status));
+    libc::WEXITSTATUS(status)
+}
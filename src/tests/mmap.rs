@@ -0,0 +1,284 @@
+/// mmap_01: Read existing data through a MAP_SHARED mapping
+#[test]
+fn mmap_01() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_01.txt");
+    crate::create_file_rw(&mut path, &pattern(2 * page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd > 0);
+
+    let addr = map(fd, 2 * page, libc::PROT_READ, libc::MAP_SHARED);
+    let data = unsafe { std::slice::from_raw_parts(addr, 2 * page) };
+    assert_eq!(data, pattern(2 * page));
+
+    unmap(addr, 2 * page);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// mmap_02: Writes through a MAP_SHARED mapping are visible to read and pread
+#[test]
+fn mmap_02() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_02.txt");
+    crate::create_file_rw(&mut path, &pattern(page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let addr = map(
+        fd,
+        page,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+    );
+    let data = unsafe { std::slice::from_raw_parts_mut(addr, page) };
+    data[100..113].copy_from_slice("Hello, World!".as_bytes());
+
+    let mut bytes = vec![0u8; 13];
+    let len = unsafe {
+        libc::pread(fd, bytes.as_mut_ptr() as *mut libc::c_void, 13, 100)
+    };
+    assert_eq!(len, 13);
+    assert_eq!(bytes, "Hello, World!".as_bytes());
+
+    let fd2 = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd2 > 0);
+
+    let mut bytes = vec![0u8; page];
+    let len = unsafe {
+        libc::read(fd2, bytes.as_mut_ptr() as *mut libc::c_void, page)
+    };
+    assert_eq!(len, page as isize);
+    assert_eq!(&bytes[100..113], "Hello, World!".as_bytes());
+
+    unmap(addr, page);
+
+    for fd in [fd, fd2] {
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+    }
+}
+
+/// mmap_03: Writes flushed with msync(MS_SYNC) are persisted
+#[test]
+fn mmap_03() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_03.txt");
+    crate::create_file_rw(&mut path, &pattern(page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let addr = map(
+        fd,
+        page,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+    );
+    let data = unsafe { std::slice::from_raw_parts_mut(addr, page) };
+    data[..13].copy_from_slice("Hello, World!".as_bytes());
+
+    let err =
+        unsafe { libc::msync(addr as *mut libc::c_void, page, libc::MS_SYNC) };
+    assert_eq!(err, 0);
+
+    unmap(addr, page);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    let contents = crate::read_file(&mut path);
+    assert_eq!(contents.len(), page);
+    assert!(contents.starts_with("Hello, World!"));
+}
+
+/// mmap_04: Writes scheduled with msync(MS_ASYNC) are persisted after munmap
+#[test]
+fn mmap_04() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_04.txt");
+    crate::create_file_rw(&mut path, &pattern(page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let addr = map(
+        fd,
+        page,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+    );
+    let data = unsafe { std::slice::from_raw_parts_mut(addr, page) };
+    data[..13].copy_from_slice("Hello, World!".as_bytes());
+
+    let err =
+        unsafe { libc::msync(addr as *mut libc::c_void, page, libc::MS_ASYNC) };
+    assert_eq!(err, 0);
+
+    unmap(addr, page);
+
+    let err = unsafe { libc::fsync(fd) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    let contents = crate::read_file(&mut path);
+    assert_eq!(contents.len(), page);
+    assert!(contents.starts_with("Hello, World!"));
+}
+
+/// mmap_05: Writes via pwrite are visible through an existing mapping
+#[test]
+fn mmap_05() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_05.txt");
+    crate::create_file_rw(&mut path, &pattern(page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let addr = map(fd, page, libc::PROT_READ, libc::MAP_SHARED);
+    let data = unsafe { std::slice::from_raw_parts(addr, page) };
+    assert_eq!(data, pattern(page));
+
+    let len = unsafe {
+        libc::pwrite(
+            fd,
+            "Hello, World!".as_ptr() as *const libc::c_void,
+            13,
+            200,
+        )
+    };
+    assert_eq!(len, 13);
+
+    let data = unsafe { std::slice::from_raw_parts(addr, page) };
+    assert_eq!(&data[200..213], "Hello, World!".as_bytes());
+
+    unmap(addr, page);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// mmap_06: Accessing a mapping beyond a truncated EOF raises SIGBUS
+#[test]
+fn mmap_06() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_06.txt");
+    crate::create_file_rw(&mut path, &pattern(2 * page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let addr = map(fd, 2 * page, libc::PROT_READ, libc::MAP_SHARED);
+
+    let err = unsafe { libc::ftruncate(fd, page as libc::off_t) };
+    assert_eq!(err, 0);
+
+    // The fault kills the process, so it has to happen in a child.
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+
+    if pid == 0 {
+        unsafe {
+            std::ptr::read_volatile(addr.add(page));
+            libc::_exit(0);
+        }
+    }
+
+    let mut status = 0;
+    let ret = unsafe { libc::waitpid(pid, &mut status, 0) };
+    assert_eq!(ret, pid);
+    assert!(libc::WIFSIGNALED(status));
+    assert_eq!(libc::WTERMSIG(status), libc::SIGBUS);
+
+    // The page before EOF is still accessible.
+    let data = unsafe { std::slice::from_raw_parts(addr, page) };
+    assert_eq!(data, pattern(page));
+
+    unmap(addr, 2 * page);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// mmap_07: Writes to a MAP_PRIVATE mapping are not written to the file
+#[test]
+fn mmap_07() {
+    let page = page_size();
+    let mut path = crate::test_dir();
+    path.push("mmap_07.txt");
+    crate::create_file_rw(&mut path, &pattern(page));
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let private = map(
+        fd,
+        page,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE,
+    );
+    let shared = map(fd, page, libc::PROT_READ, libc::MAP_SHARED);
+
+    let data = unsafe { std::slice::from_raw_parts_mut(private, page) };
+    data[..13].copy_from_slice("Hello, World!".as_bytes());
+
+    let err = unsafe {
+        libc::msync(private as *mut libc::c_void, page, libc::MS_SYNC)
+    };
+    assert_eq!(err, 0);
+
+    let data = unsafe { std::slice::from_raw_parts(shared, page) };
+    assert_eq!(data, pattern(page));
+
+    unmap(private, page);
+    unmap(shared, page);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    assert_eq!(crate::read_file(&mut path).as_bytes(), pattern(page));
+}
+
+// Mappings are made of whole pages, which are larger than 4K on some kernels.
+fn page_size() -> usize {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    assert!(size > 0);
+    size as usize
+}
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| 97 + (i % 26) as u8).collect()
+}
+
+// Map `len` bytes of `fd`, marking the test unsupported if the filesystem
+// can't be memory mapped.
+fn map(fd: i32, len: usize, prot: i32, flags: i32) -> *mut u8 {
+    let addr =
+        unsafe { libc::mmap(std::ptr::null_mut(), len, prot, flags, fd, 0) };
+    if addr == libc::MAP_FAILED {
+        let errno = crate::errno();
+        if errno == libc::ENODEV {
+            crate::unsupported("mmap", errno);
+        }
+        crate::check_supported("mmap", errno);
+    }
+    assert_ne!(addr, libc::MAP_FAILED);
+    addr as *mut u8
+}
+
+fn unmap(addr: *mut u8, len: usize) {
+    let err = unsafe { libc::munmap(addr as *mut libc::c_void, len) };
+    assert_eq!(err, 0);
+}
@@ -7,8 +7,20 @@ pub mod file_pread;
 pub mod file_pwrite;
 pub mod file_read;
 pub mod file_write;
+pub mod locks;
+pub mod mmap;
+pub mod names;
 pub mod properties;
+pub mod readdir;
+pub mod rename;
 pub mod special;
+#[cfg(target_os = "linux")]
+pub mod statx;
+pub mod times;
+#[cfg(target_os = "linux")]
+pub mod xattr;
 
-//const DATA_SIZE: usize = 1024 * 1024 * 15;
-const DATA_SIZE: usize = 1024 * 1024 * 15;
+/// Size of the files written and read by the data tests, set by `data_size`.
+fn data_size() -> usize {
+    crate::config::config().data_size
+}
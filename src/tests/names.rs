@@ -0,0 +1,371 @@
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+
+use crate::TestPath;
+
+// Name and path length limits from linux/limits.h.
+const NAME_MAX: usize = 255;
+#[cfg(target_os = "linux")]
+const PATH_MAX: usize = 4096;
+
+/// name_01: Create, stat and list a file and a directory with NAME_MAX bytes
+#[test]
+fn name_01() {
+    let mut dir = crate::create_test_subdir("name_01");
+
+    let file = vec![b'f'; NAME_MAX];
+    let subdir = vec![b'd'; NAME_MAX];
+
+    let mut path = join(&dir, &file);
+    crate::create_empty_file(&mut path);
+    assert_eq!(crate::stat(&mut path).st_mode & libc::S_IFMT, libc::S_IFREG);
+
+    let mut path = join(&dir, &subdir);
+    let err = unsafe { libc::mkdir(path.c_str(), 0o700) };
+    assert_eq!(err, 0);
+    assert_eq!(crate::stat(&mut path).st_mode & libc::S_IFMT, libc::S_IFDIR);
+
+    let mut names = vec![file, subdir];
+    names.sort();
+    assert_eq!(list(&mut dir), names);
+}
+
+/// name_02: Names longer than NAME_MAX fail with ENAMETOOLONG
+#[test]
+fn name_02() {
+    let mut dir = crate::create_test_subdir("name_02");
+    let mut path = join(&dir, &[b'a'; NAME_MAX + 1]);
+
+    let fd = unsafe {
+        crate::open3(
+            path.c_str(),
+            libc::O_WRONLY | libc::O_CREAT,
+            libc::S_IRUSR | libc::S_IWUSR,
+        )
+    };
+    assert_eq!(fd, -1);
+    assert_eq!(crate::errno(), libc::ENAMETOOLONG);
+
+    let err = unsafe { libc::mkdir(path.c_str(), 0o700) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::ENAMETOOLONG);
+
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    let err = unsafe { libc::stat(path.c_str(), &mut stat) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::ENAMETOOLONG);
+
+    assert!(list(&mut dir).is_empty());
+}
+
+/// name_03: Paths of PATH_MAX - 1 bytes work and longer paths don't
+#[cfg(target_os = "linux")]
+#[test]
+fn name_03() {
+    let dir = crate::create_test_subdir("name_03");
+    let mut path = dir.clone();
+
+    // Nest directories until there's room left for a single file name, which
+    // then fills the path to exactly PATH_MAX - 1 bytes plus the NUL.
+    let mut depth = 0;
+    while PATH_MAX - 1 - path_len(&mut path) > NAME_MAX {
+        path.push(format!("{depth:03}_{}", "d".repeat(196)));
+        let err = unsafe { libc::mkdir(path.c_str(), 0o700) };
+        assert_eq!(err, 0);
+        depth += 1;
+    }
+
+    let remaining = PATH_MAX - 1 - path_len(&mut path) - 1;
+    path.push("f".repeat(remaining));
+    assert_eq!(path_len(&mut path), PATH_MAX - 1);
+
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+    assert_eq!(crate::read_file(&mut path), "Hello, World!");
+
+    path.pop();
+    path.push("f".repeat(remaining + 1));
+
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    let err = unsafe { libc::stat(path.c_str(), &mut stat) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::ENAMETOOLONG);
+}
+
+/// name_04: Names that aren't valid UTF-8 are stored and listed verbatim
+#[test]
+fn name_04() {
+    let mut dir = crate::create_test_subdir("name_04");
+
+    let mut names = vec![
+        b"name_04_\xff\xfe".to_vec(),
+        b"name_04_\x80".to_vec(),
+        b"name_04_\xc3\x28".to_vec(),
+        b"name_04_latin1_caf\xe9".to_vec(),
+    ];
+
+    for name in names.iter() {
+        let mut path = join(&dir, name);
+        let fd = unsafe {
+            crate::open3(
+                path.c_str(),
+                libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+                libc::S_IRUSR | libc::S_IWUSR,
+            )
+        };
+
+        // Filesystems that store names as UTF-8 or UTF-16 reject these.
+        if fd < 0 {
+            let errno = crate::errno();
+            if errno == libc::EILSEQ || errno == libc::EINVAL {
+                crate::unsupported("non-UTF-8 names", errno);
+            }
+        }
+        assert!(fd > 0);
+
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+    }
+
+    names.sort();
+    assert_eq!(list(&mut dir), names);
+
+    for name in names.iter() {
+        let mut path = join(&dir, name);
+        let err = unsafe { libc::unlink(path.c_str()) };
+        assert_eq!(err, 0);
+    }
+
+    assert!(list(&mut dir).is_empty());
+}
+
+/// name_05: Names with newlines, tabs and other control characters
+#[test]
+fn name_05() {
+    let mut dir = crate::create_test_subdir("name_05");
+
+    let mut names = [
+        "line\nbreak",
+        "\nleading_newline",
+        "trailing_newline\n",
+        "carriage\rreturn",
+        "tab\tseparated",
+        "bell\x07",
+        "escape\x1b[0m",
+        "delete\x7f",
+    ]
+    .iter()
+    .map(|name| name.as_bytes().to_vec())
+    .collect::<Vec<_>>();
+
+    for name in names.iter() {
+        crate::create_file_rw(&mut join(&dir, name), name);
+    }
+
+    names.sort();
+    assert_eq!(list(&mut dir), names);
+
+    for name in names.iter() {
+        let contents = crate::read_file(&mut join(&dir, name));
+        assert_eq!(contents.as_bytes(), name);
+    }
+}
+
+/// name_06: Names with leading dashes are ordinary names
+#[test]
+fn name_06() {
+    let mut dir = crate::create_test_subdir("name_06");
+
+    let mut names = ["-", "--", "-rf", "--help", "-n", "- space"]
+        .iter()
+        .map(|name| name.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+
+    for name in names.iter() {
+        crate::create_file_rw(&mut join(&dir, name), name);
+    }
+
+    names.sort();
+    assert_eq!(list(&mut dir), names);
+
+    for name in names.iter() {
+        let contents = crate::read_file(&mut join(&dir, name));
+        assert_eq!(contents.as_bytes(), name);
+    }
+}
+
+/// name_07: Trailing dots and spaces are part of the name
+#[test]
+fn name_07() {
+    let mut dir = crate::create_test_subdir("name_07");
+
+    // Filesystems with Windows naming rules strip trailing dots and spaces,
+    // which would make all of these the same file as `name`.
+    let mut names = [
+        "name", "name.", "name..", "name ", "name  ", "name. ", "name .",
+        " name", "...", " ",
+    ]
+    .iter()
+    .map(|name| name.as_bytes().to_vec())
+    .collect::<Vec<_>>();
+
+    for name in names.iter() {
+        crate::create_file_rw(&mut join(&dir, name), name);
+    }
+
+    names.sort();
+    assert_eq!(list(&mut dir), names);
+
+    for name in names.iter() {
+        let contents = crate::read_file(&mut join(&dir, name));
+        assert_eq!(contents.as_bytes(), name);
+    }
+}
+
+/// name_08: Names differing only in case are consistently the same or not
+#[test]
+fn name_08() {
+    let mut dir = crate::create_test_subdir("name_08");
+
+    let mut lower = join(&dir, b"name");
+    let mut upper = join(&dir, b"NAME");
+
+    crate::create_empty_file(&mut lower);
+
+    // Either `NAME` is a new file, or it's the existing `name` and every
+    // operation has to agree on that.
+    let fd = unsafe {
+        crate::open3(
+            upper.c_str(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+            libc::S_IRUSR | libc::S_IWUSR,
+        )
+    };
+
+    if fd >= 0 {
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+
+        let lower_ino = crate::stat(&mut lower).st_ino;
+        let upper_ino = crate::stat(&mut upper).st_ino;
+        assert_ne!(lower_ino, upper_ino);
+
+        assert_eq!(list(&mut dir), [b"NAME".to_vec(), b"name".to_vec()]);
+
+        let mut mixed = join(&dir, b"Name");
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        let err = unsafe { libc::stat(mixed.c_str(), &mut stat) };
+        assert_eq!(err, -1);
+        assert_eq!(crate::errno(), libc::ENOENT);
+    } else {
+        assert_eq!(crate::errno(), libc::EEXIST);
+
+        let lower_ino = crate::stat(&mut lower).st_ino;
+        let upper_ino = crate::stat(&mut upper).st_ino;
+        assert_eq!(lower_ino, upper_ino);
+
+        let names = list(&mut dir);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].eq_ignore_ascii_case(b"name"));
+    }
+}
+
+/// name_09: NFC and NFD forms of a name are consistently the same or not
+#[test]
+fn name_09() {
+    let mut dir = crate::create_test_subdir("name_09");
+
+    let nfc = "caf\u{e9}".as_bytes();
+    let nfd = "cafe\u{301}".as_bytes();
+
+    let mut composed = join(&dir, nfc);
+    let mut decomposed = join(&dir, nfd);
+
+    crate::create_empty_file(&mut composed);
+
+    // Either the forms are distinct names, or the filesystem normalizes them
+    // and both find the same file.
+    let fd = unsafe {
+        crate::open3(
+            decomposed.c_str(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+            libc::S_IRUSR | libc::S_IWUSR,
+        )
+    };
+
+    if fd >= 0 {
+        let err = unsafe { libc::close(fd) };
+        assert_eq!(err, 0);
+
+        let composed_ino = crate::stat(&mut composed).st_ino;
+        let decomposed_ino = crate::stat(&mut decomposed).st_ino;
+        assert_ne!(composed_ino, decomposed_ino);
+
+        let mut names = vec![nfc.to_vec(), nfd.to_vec()];
+        names.sort();
+        assert_eq!(list(&mut dir), names);
+    } else {
+        assert_eq!(crate::errno(), libc::EEXIST);
+
+        let composed_ino = crate::stat(&mut composed).st_ino;
+        let decomposed_ino = crate::stat(&mut decomposed).st_ino;
+        assert_eq!(composed_ino, decomposed_ino);
+
+        let names = list(&mut dir);
+        assert_eq!(names.len(), 1);
+        assert!(names[0] == nfc || names[0] == nfd);
+    }
+}
+
+/// name_10: Renaming a file to a different case of its own name
+#[test]
+fn name_10() {
+    let mut dir = crate::create_test_subdir("name_10");
+
+    let mut src = join(&dir, b"name");
+    let mut dst = join(&dir, b"NAME");
+
+    crate::create_file_rw(&mut src, "Hello, World!".as_bytes());
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    assert_eq!(list(&mut dir), [b"NAME".to_vec()]);
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+}
+
+fn join(dir: &TestPath, name: &[u8]) -> TestPath {
+    let mut path = dir.clone();
+    path.push(OsStr::from_bytes(name));
+    path
+}
+
+#[cfg(target_os = "linux")]
+fn path_len(path: &mut TestPath) -> usize {
+    unsafe { CStr::from_ptr(path.c_str()) }.to_bytes().len()
+}
+
+// List the names in `dir`, sorted and without `.` and `..`.
+fn list(dir: &mut TestPath) -> Vec<Vec<u8>> {
+    let dirp = unsafe { libc::opendir(dir.c_str()) };
+    assert_ne!(dirp, std::ptr::null_mut());
+
+    let mut names = Vec::new();
+    loop {
+        let entry = unsafe { libc::readdir(dirp) };
+        if entry.is_null() {
+            break;
+        }
+
+        let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+        let name = name.to_bytes();
+        if name != b"." && name != b".." {
+            names.push(name.to_vec());
+        }
+    }
+
+    let err = unsafe { libc::closedir(dirp) };
+    assert_eq!(err, 0);
+
+    names.sort();
+    names
+}
@@ -0,0 +1,427 @@
+use std::ffi::CStr;
+
+use crate::TestPath;
+
+// Large enough to need many getdents64 calls with any reasonable buffer.
+const LARGE_DIR: usize = 10_000;
+
+/// readdir_01: List a directory with 10,000 entries
+#[test]
+fn readdir_01() {
+    let mut dir = crate::create_test_subdir("readdir_01");
+    let names = populate(&mut dir, "readdir_01", LARGE_DIR);
+
+    let dirp = open_dir(&mut dir);
+    let found = read_names(dirp);
+    close_dir(dirp);
+
+    assert_eq!(found, with_dots(names));
+}
+
+/// readdir_02: List a large directory in several getdents64 batches
+#[cfg(target_os = "linux")]
+#[test]
+fn readdir_02() {
+    let mut dir = crate::create_test_subdir("readdir_02");
+    let names = populate(&mut dir, "readdir_02", LARGE_DIR);
+
+    let fd =
+        unsafe { libc::open(dir.c_str(), libc::O_RDONLY | libc::O_DIRECTORY) };
+    assert!(fd > 0);
+
+    let mut buf = vec![0u8; 4096];
+    let mut found = Vec::new();
+    let mut batches = 0;
+    loop {
+        let len = unsafe {
+            libc::syscall(
+                libc::SYS_getdents64,
+                fd,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+            )
+        };
+        if len < 0 {
+            crate::check_supported("getdents64", crate::errno());
+        }
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+
+        batches += 1;
+
+        // struct linux_dirent64 is d_ino (u64), d_off (i64), d_reclen (u16),
+        // d_type (u8) followed by the NUL terminated name.
+        let mut offset = 0;
+        while offset < len as usize {
+            let record = &buf[offset..];
+            let reclen = u16::from_ne_bytes([record[16], record[17]]) as usize;
+            let name = CStr::from_bytes_until_nul(&record[19..reclen])
+                .expect("Unterminated name in getdents64 record");
+            found.push(name.to_bytes().to_vec());
+            offset += reclen;
+        }
+    }
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    assert!(batches > 1);
+
+    found.sort();
+    assert_eq!(found, with_dots(names));
+}
+
+/// readdir_03: seekdir to a position from telldir repeats the same entries
+#[test]
+fn readdir_03() {
+    let mut dir = crate::create_test_subdir("readdir_03");
+    populate(&mut dir, "readdir_03", 1000);
+
+    let dirp = open_dir(&mut dir);
+
+    for _ in 0..500 {
+        assert!(next_name(dirp).is_some());
+    }
+
+    let loc = unsafe { libc::telldir(dirp) };
+    assert!(loc >= 0);
+
+    let mut first = Vec::new();
+    while let Some(name) = next_name(dirp) {
+        first.push(name);
+    }
+    assert!(!first.is_empty());
+
+    unsafe { libc::seekdir(dirp, loc) };
+
+    let mut second = Vec::new();
+    while let Some(name) = next_name(dirp) {
+        second.push(name);
+    }
+
+    close_dir(dirp);
+
+    assert_eq!(second, first);
+}
+
+/// readdir_04: rewinddir restarts the listing and sees new entries
+#[test]
+fn readdir_04() {
+    let mut dir = crate::create_test_subdir("readdir_04");
+    let mut names = populate(&mut dir, "readdir_04", 100);
+
+    let dirp = open_dir(&mut dir);
+    assert_eq!(read_names(dirp), with_dots(names.clone()));
+
+    dir.push("readdir_04_new");
+    crate::create_empty_file(&mut dir);
+    dir.pop();
+    names.push(b"readdir_04_new".to_vec());
+
+    unsafe { libc::rewinddir(dirp) };
+    let found = read_names(dirp);
+    close_dir(dirp);
+
+    assert_eq!(found, with_dots(names));
+}
+
+/// readdir_05: Unlinking each entry as it's listed returns every entry once
+#[test]
+fn readdir_05() {
+    let mut dir = crate::create_test_subdir("readdir_05");
+    let names = populate(&mut dir, "readdir_05", 1000);
+
+    let dirp = open_dir(&mut dir);
+
+    let mut found = Vec::new();
+    while let Some(name) = next_name(dirp) {
+        if name != b"." && name != b".." {
+            let mut path = dir.clone();
+            path.push(String::from_utf8(name.clone()).unwrap());
+            let err = unsafe { libc::unlink(path.c_str()) };
+            assert_eq!(err, 0);
+        }
+        found.push(name);
+    }
+
+    close_dir(dirp);
+
+    found.sort();
+    assert_eq!(found, with_dots(names));
+
+    let dirp = open_dir(&mut dir);
+    assert_eq!(read_names(dirp), with_dots(Vec::new()));
+    close_dir(dirp);
+}
+
+/// readdir_06: Entries removed during a listing don't disturb the others
+#[test]
+fn readdir_06() {
+    let mut dir = crate::create_test_subdir("readdir_06");
+    let names = populate(&mut dir, "readdir_06", 1000);
+
+    let dirp = open_dir(&mut dir);
+
+    // Remove every other entry part way through the listing. Whether removed
+    // entries that weren't listed yet are returned is unspecified, but every
+    // remaining entry must be returned exactly once.
+    let mut found = Vec::new();
+    for _ in 0..100 {
+        found.push(next_name(dirp).expect("Directory ended early"));
+    }
+
+    let (removed, kept): (Vec<_>, Vec<_>) = names
+        .into_iter()
+        .enumerate()
+        .partition(|(idx, _)| idx % 2 == 0);
+
+    for (_, name) in removed.iter() {
+        let mut path = dir.clone();
+        path.push(String::from_utf8(name.clone()).unwrap());
+        let err = unsafe { libc::unlink(path.c_str()) };
+        assert_eq!(err, 0);
+    }
+
+    while let Some(name) = next_name(dirp) {
+        found.push(name);
+    }
+
+    close_dir(dirp);
+
+    let removed = removed.into_iter().map(|(_, n)| n).collect::<Vec<_>>();
+    let kept = kept.into_iter().map(|(_, n)| n).collect::<Vec<_>>();
+
+    for name in with_dots(kept) {
+        let count = found.iter().filter(|n| **n == name).count();
+        assert_eq!(count, 1, "{}", String::from_utf8_lossy(&name));
+    }
+
+    for name in removed {
+        let count = found.iter().filter(|n| **n == name).count();
+        assert!(count <= 1, "{}", String::from_utf8_lossy(&name));
+    }
+}
+
+/// readdir_07: Entries created during a listing don't disturb the others
+#[test]
+fn readdir_07() {
+    let mut dir = crate::create_test_subdir("readdir_07");
+    let names = populate(&mut dir, "readdir_07", 1000);
+
+    let dirp = open_dir(&mut dir);
+
+    // Whether new entries are returned is unspecified, but they must not be
+    // returned twice and every existing entry must be returned exactly once.
+    let mut found = Vec::new();
+    let mut added = Vec::new();
+    while let Some(name) = next_name(dirp) {
+        found.push(name);
+        if found.len() % 10 == 0 {
+            let name = format!("readdir_07_new_{}", found.len());
+            dir.push(&name);
+            crate::create_empty_file(&mut dir);
+            dir.pop();
+            added.push(name.into_bytes());
+        }
+    }
+
+    close_dir(dirp);
+
+    for name in with_dots(names) {
+        let count = found.iter().filter(|n| **n == name).count();
+        assert_eq!(count, 1, "{}", String::from_utf8_lossy(&name));
+    }
+
+    for name in added {
+        let count = found.iter().filter(|n| **n == name).count();
+        assert!(count <= 1, "{}", String::from_utf8_lossy(&name));
+    }
+}
+
+/// readdir_08: d_type matches the file type reported by lstat
+#[test]
+fn readdir_08() {
+    let mut dir = crate::create_test_subdir("readdir_08");
+
+    dir.push("file");
+    crate::create_empty_file(&mut dir);
+    dir.pop();
+
+    dir.push("dir");
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+    dir.pop();
+
+    dir.push("symlink");
+    let err = unsafe { libc::symlink(c"file".as_ptr(), dir.c_str()) };
+    assert_eq!(err, 0);
+    dir.pop();
+
+    dir.push("fifo");
+    let err = unsafe { libc::mkfifo(dir.c_str(), 0o600) };
+    if err != 0 {
+        crate::check_supported("mkfifo", crate::errno());
+    }
+    assert_eq!(err, 0);
+    dir.pop();
+
+    let dirp = open_dir(&mut dir);
+
+    let mut checked = 0;
+    loop {
+        let entry = unsafe { libc::readdir(dirp) };
+        if entry.is_null() {
+            break;
+        }
+
+        // DT_UNKNOWN is always allowed, callers have to fall back to lstat.
+        let d_type = unsafe { (*entry).d_type };
+        if d_type == libc::DT_UNKNOWN {
+            continue;
+        }
+
+        let mut path = dir.clone();
+        path.push(entry_name(entry));
+        let expected = match crate::lstat(&mut path).st_mode & libc::S_IFMT {
+            libc::S_IFREG => libc::DT_REG,
+            libc::S_IFDIR => libc::DT_DIR,
+            libc::S_IFLNK => libc::DT_LNK,
+            libc::S_IFIFO => libc::DT_FIFO,
+            mode => panic!("Unexpected file type: {mode:o}"),
+        };
+        assert_eq!(d_type, expected, "{}", entry_name(entry));
+        checked += 1;
+    }
+
+    close_dir(dirp);
+
+    if checked == 0 {
+        crate::unsupported("d_type", libc::ENOTSUP);
+    }
+}
+
+/// readdir_09: d_ino matches the inode number reported by lstat
+#[test]
+fn readdir_09() {
+    let mut dir = crate::create_test_subdir("readdir_09");
+    populate(&mut dir, "readdir_09", 100);
+
+    dir.push("dir");
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+    dir.pop();
+
+    let dirp = open_dir(&mut dir);
+
+    loop {
+        let entry = unsafe { libc::readdir(dirp) };
+        if entry.is_null() {
+            break;
+        }
+
+        // The parent may be a mount point, where d_ino is the inode of the
+        // covered directory rather than the root of the mounted filesystem.
+        let name = entry_name(entry);
+        if name == ".." {
+            continue;
+        }
+
+        let mut path = dir.clone();
+        path.push(&name);
+        let d_ino = unsafe { (*entry).d_ino };
+        assert_eq!(d_ino, crate::lstat(&mut path).st_ino, "{name}");
+    }
+
+    close_dir(dirp);
+}
+
+/// readdir_10: Names with unicode, spaces and 255 bytes are listed intact
+#[test]
+fn readdir_10() {
+    let mut dir = crate::create_test_subdir("readdir_10");
+
+    let mut names = vec![
+        "héllo wörld".to_owned(),
+        "日本語のファイル名".to_owned(),
+        "emoji 🦀🚀".to_owned(),
+        " leading and trailing ".to_owned(),
+        "a".repeat(255),
+        // 85 three byte characters, also 255 bytes.
+        "語".repeat(85),
+    ];
+
+    for name in names.iter() {
+        dir.push(name);
+        crate::create_empty_file(&mut dir);
+        dir.pop();
+    }
+
+    let dirp = open_dir(&mut dir);
+    let found = read_names(dirp);
+    close_dir(dirp);
+
+    names.sort();
+    let names = names.into_iter().map(String::into_bytes).collect();
+    assert_eq!(found, with_dots(names));
+}
+
+// Create `count` empty files in `dir` and return their sorted names.
+fn populate(dir: &mut TestPath, prefix: &str, count: usize) -> Vec<Vec<u8>> {
+    let mut names = (0..count)
+        .map(|idx| format!("{prefix}_{idx:05}"))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    for name in names.iter() {
+        dir.push(name);
+        crate::create_empty_file(dir);
+        dir.pop();
+    }
+
+    names.into_iter().map(String::into_bytes).collect()
+}
+
+fn open_dir(dir: &mut TestPath) -> *mut libc::DIR {
+    let dirp = unsafe { libc::opendir(dir.c_str()) };
+    assert_ne!(dirp, std::ptr::null_mut());
+    dirp
+}
+
+fn close_dir(dirp: *mut libc::DIR) {
+    let err = unsafe { libc::closedir(dirp) };
+    assert_eq!(err, 0);
+}
+
+fn next_name(dirp: *mut libc::DIR) -> Option<Vec<u8>> {
+    let entry = unsafe { libc::readdir(dirp) };
+    if entry.is_null() {
+        return None;
+    }
+
+    let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+    Some(name.to_bytes().to_vec())
+}
+
+fn entry_name(entry: *const libc::dirent) -> String {
+    let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+    name.to_string_lossy().to_string()
+}
+
+// Read the rest of the directory and return the sorted names.
+fn read_names(dirp: *mut libc::DIR) -> Vec<Vec<u8>> {
+    let mut names = Vec::new();
+    while let Some(name) = next_name(dirp) {
+        names.push(name);
+    }
+    names.sort();
+    names
+}
+
+fn with_dots(mut names: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    names.push(b".".to_vec());
+    names.push(b"..".to_vec());
+    names.sort();
+    names
+}
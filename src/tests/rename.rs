@@ -0,0 +1,375 @@
+use crate::TestPath;
+
+/// rename_01: Rename over an existing file replaces it
+#[test]
+fn rename_01() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_01_src.txt");
+    dst.push("rename_01_dst.txt");
+
+    crate::create_file(&mut src, "source".as_bytes());
+    crate::create_file(&mut dst, "target".as_bytes());
+
+    // A reader of the old target keeps seeing its contents.
+    let fd = unsafe { libc::open(dst.c_str(), libc::O_RDONLY) };
+    assert!(fd > 0);
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    assert!(!exists(&mut src));
+    assert_eq!(crate::read_file(&mut dst), "source");
+
+    let mut bytes = vec![0u8; 1024];
+    let len = unsafe {
+        libc::read(fd, bytes.as_mut_ptr() as *mut libc::c_void, bytes.len())
+    };
+    assert_eq!(len, 6);
+    assert_eq!(&bytes[..6], "target".as_bytes());
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// rename_02: Rename a file into another directory
+#[test]
+fn rename_02() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_02_src.txt");
+    dst.push("rename_02");
+
+    let err = unsafe { libc::mkdir(dst.c_str(), 0o700) };
+    assert_eq!(err, 0);
+    dst.push("rename_02_dst.txt");
+
+    crate::create_file(&mut src, "Hello, World!".as_bytes());
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    assert!(!exists(&mut src));
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+}
+
+/// rename_03: Rename a directory with contents
+#[test]
+fn rename_03() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_03_src");
+    dst.push("rename_03_dst");
+
+    let err = unsafe { libc::mkdir(src.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    src.push("sub");
+    let err = unsafe { libc::mkdir(src.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    src.push("rename_03.txt");
+    crate::create_file(&mut src, "Hello, World!".as_bytes());
+    src.pop();
+    src.pop();
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    assert!(!exists(&mut src));
+
+    dst.push("sub");
+    dst.push("rename_03.txt");
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+}
+
+/// rename_04: Rename a directory onto a non-empty directory fails
+#[test]
+fn rename_04() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_04_src");
+    dst.push("rename_04_dst");
+
+    let err = unsafe { libc::mkdir(src.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::mkdir(dst.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    dst.push("rename_04.txt");
+    crate::create_file(&mut dst, &[]);
+    dst.pop();
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, -1);
+
+    // POSIX allows either error, Linux uses ENOTEMPTY.
+    let errno = crate::errno();
+    assert!(errno == libc::ENOTEMPTY || errno == libc::EEXIST);
+
+    assert!(exists(&mut src));
+}
+
+/// rename_05: Rename a directory onto an empty directory replaces it
+#[test]
+fn rename_05() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_05_src");
+    dst.push("rename_05_dst");
+
+    let err = unsafe { libc::mkdir(src.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    src.push("rename_05.txt");
+    crate::create_file(&mut src, "Hello, World!".as_bytes());
+    src.pop();
+
+    let err = unsafe { libc::mkdir(dst.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    assert!(!exists(&mut src));
+
+    dst.push("rename_05.txt");
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+}
+
+/// rename_06: Rename a file or directory onto itself does nothing
+#[test]
+fn rename_06() {
+    let mut file = crate::test_dir();
+    let mut dir = file.clone();
+    file.push("rename_06.txt");
+    dir.push("rename_06");
+
+    crate::create_file(&mut file, "Hello, World!".as_bytes());
+
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let mut same = file.clone();
+    let err = unsafe { libc::rename(file.c_str(), same.c_str()) };
+    assert_eq!(err, 0);
+    assert_eq!(crate::read_file(&mut file), "Hello, World!");
+
+    let mut same = dir.clone();
+    let err = unsafe { libc::rename(dir.c_str(), same.c_str()) };
+    assert_eq!(err, 0);
+    assert!(exists(&mut dir));
+}
+
+/// rename_07: Rename a directory into its own subtree fails with EINVAL
+#[test]
+fn rename_07() {
+    let mut src = crate::test_dir();
+    src.push("rename_07");
+
+    let err = unsafe { libc::mkdir(src.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let mut dst = src.clone();
+    dst.push("sub");
+    let err = unsafe { libc::mkdir(dst.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    dst.push("moved");
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EINVAL);
+}
+
+/// rename_08: Rename between hard links of the same file does nothing
+#[test]
+fn rename_08() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_08_src.txt");
+    dst.push("rename_08_dst.txt");
+
+    crate::create_file(&mut src, "Hello, World!".as_bytes());
+
+    let err = unsafe { libc::link(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    // Both names remain.
+    assert!(exists(&mut src));
+    assert!(exists(&mut dst));
+    assert_eq!(crate::stat(&mut dst).st_nlink, 2);
+}
+
+/// rename_09: Rename a file onto a directory or a directory onto a file
+#[test]
+fn rename_09() {
+    let mut file = crate::test_dir();
+    let mut dir = file.clone();
+    file.push("rename_09.txt");
+    dir.push("rename_09");
+
+    crate::create_file(&mut file, &[]);
+
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::rename(file.c_str(), dir.c_str()) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EISDIR);
+
+    let err = unsafe { libc::rename(dir.c_str(), file.c_str()) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::ENOTDIR);
+}
+
+/// rename_10: An open fd keeps working after its file is renamed
+#[test]
+fn rename_10() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_10_src.txt");
+    dst.push("rename_10_dst.txt");
+
+    crate::create_file_rw(&mut src, "Hello, ".as_bytes());
+
+    let fd =
+        unsafe { libc::open(src.c_str(), libc::O_WRONLY | libc::O_APPEND) };
+    assert!(fd > 0);
+
+    let err = unsafe { libc::rename(src.c_str(), dst.c_str()) };
+    assert_eq!(err, 0);
+
+    let len =
+        unsafe { libc::write(fd, "World!".as_ptr() as *const libc::c_void, 6) };
+    assert_eq!(len, 6);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+}
+
+/// rename_11: RENAME_NOREPLACE fails with EEXIST if the target exists
+#[cfg(target_os = "linux")]
+#[test]
+fn rename_11() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    let mut new = src.clone();
+    src.push("rename_11_src.txt");
+    dst.push("rename_11_dst.txt");
+    new.push("rename_11_new.txt");
+
+    crate::create_file(&mut src, "source".as_bytes());
+    crate::create_file(&mut dst, "target".as_bytes());
+
+    let err = renameat2(&mut src, &mut dst, libc::RENAME_NOREPLACE);
+    if err == -1 {
+        crate::check_flag_supported("RENAME_NOREPLACE", crate::errno());
+    }
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EEXIST);
+    assert_eq!(crate::read_file(&mut dst), "target");
+
+    let err = renameat2(&mut src, &mut new, libc::RENAME_NOREPLACE);
+    assert_eq!(err, 0);
+    assert!(!exists(&mut src));
+    assert_eq!(crate::read_file(&mut new), "source");
+}
+
+/// rename_12: RENAME_EXCHANGE swaps a file and a directory
+#[cfg(target_os = "linux")]
+#[test]
+fn rename_12() {
+    let mut file = crate::test_dir();
+    let mut dir = file.clone();
+    file.push("rename_12_a");
+    dir.push("rename_12_b");
+
+    crate::create_file(&mut file, "Hello, World!".as_bytes());
+
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let err = renameat2(&mut file, &mut dir, libc::RENAME_EXCHANGE);
+    if err == -1 {
+        crate::check_flag_supported("RENAME_EXCHANGE", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let st = crate::stat(&mut file);
+    assert_eq!(st.st_mode & libc::S_IFMT, libc::S_IFDIR);
+    assert_eq!(crate::read_file(&mut dir), "Hello, World!");
+}
+
+/// rename_13: RENAME_EXCHANGE fails with ENOENT if the target is missing
+#[cfg(target_os = "linux")]
+#[test]
+fn rename_13() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_13_src.txt");
+    dst.push("rename_13_dst.txt");
+
+    crate::create_file(&mut src, &[]);
+
+    let err = renameat2(&mut src, &mut dst, libc::RENAME_EXCHANGE);
+    assert_eq!(err, -1);
+    crate::check_flag_supported("RENAME_EXCHANGE", crate::errno());
+    assert_eq!(crate::errno(), libc::ENOENT);
+    assert!(exists(&mut src));
+}
+
+/// rename_14: RENAME_WHITEOUT leaves a whiteout device behind
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "needs CAP_MKNOD, run as root with --ignored"]
+fn rename_14() {
+    let mut src = crate::test_dir();
+    let mut dst = src.clone();
+    src.push("rename_14_src.txt");
+    dst.push("rename_14_dst.txt");
+
+    crate::create_file(&mut src, "Hello, World!".as_bytes());
+
+    // Creating the whiteout device needs CAP_MKNOD. Failing with EPERM says
+    // nothing about the filesystem, so it isn't reported as unsupported.
+    let err = renameat2(&mut src, &mut dst, libc::RENAME_WHITEOUT);
+    if err == -1 {
+        let errno = crate::errno();
+        assert_ne!(errno, libc::EPERM, "RENAME_WHITEOUT needs CAP_MKNOD");
+        crate::check_flag_supported("RENAME_WHITEOUT", errno);
+    }
+    assert_eq!(err, 0);
+
+    assert_eq!(crate::read_file(&mut dst), "Hello, World!");
+
+    let st = crate::lstat(&mut src);
+    assert_eq!(st.st_mode & libc::S_IFMT, libc::S_IFCHR);
+    assert_eq!(st.st_rdev, 0);
+}
+
+fn exists(path: &mut TestPath) -> bool {
+    let err = unsafe { libc::access(path.c_str(), libc::F_OK) };
+    if err == -1 {
+        assert_eq!(crate::errno(), libc::ENOENT);
+    }
+    err == 0
+}
+
+#[cfg(target_os = "linux")]
+fn renameat2(src: &mut TestPath, dst: &mut TestPath, flags: u32) -> i32 {
+    unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            src.c_str(),
+            libc::AT_FDCWD,
+            dst.c_str(),
+            flags,
+        )
+    }
+}
@@ -0,0 +1,314 @@
+use crate::TestPath;
+
+// Inode flags from linux/fs.h, set with FS_IOC_SETFLAGS.
+const FS_IMMUTABLE_FL: libc::c_int = 0x10;
+const FS_APPEND_FL: libc::c_int = 0x20;
+
+/// statx_01: statx agrees with stat
+#[test]
+fn statx_01() {
+    let mut path = crate::test_dir();
+    path.push("statx_01.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    assert_matches(&stx, &crate::stat(&mut path));
+}
+
+/// statx_02: statx with AT_STATX_DONT_SYNC agrees with stat
+#[test]
+fn statx_02() {
+    let mut path = crate::test_dir();
+    path.push("statx_02.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx = crate::statx(
+        &mut path,
+        libc::AT_STATX_DONT_SYNC,
+        libc::STATX_BASIC_STATS,
+    );
+    assert_matches(&stx, &crate::stat(&mut path));
+}
+
+/// statx_03: statx with AT_STATX_FORCE_SYNC sees writes from an open fd
+#[test]
+fn statx_03() {
+    let mut path = crate::test_dir();
+    path.push("statx_03.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert!(fd > 0);
+
+    let len = unsafe {
+        libc::pwrite(fd, "Goodbye".as_ptr() as *const libc::c_void, 7, 13)
+    };
+    assert_eq!(len, 7);
+
+    let stx = crate::statx(
+        &mut path,
+        libc::AT_STATX_FORCE_SYNC,
+        libc::STATX_BASIC_STATS,
+    );
+    assert_eq!(stx.stx_size, 20);
+    assert_matches(&stx, &crate::stat(&mut path));
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// statx_04: AT_SYMLINK_NOFOLLOW describes the link like lstat
+#[test]
+fn statx_04() {
+    let mut path_src = crate::test_dir();
+    let mut path_dst = path_src.clone();
+
+    path_src.push("statx_04_src.txt");
+    path_dst.push("statx_04_dst.txt");
+
+    crate::create_file_rw(&mut path_src, "Hello, World!".as_bytes());
+
+    let err = unsafe {
+        libc::symlink(c"statx_04_src.txt".as_ptr(), path_dst.c_str())
+    };
+    assert_eq!(err, 0);
+
+    let stx = crate::statx(&mut path_dst, 0, libc::STATX_BASIC_STATS);
+    assert_eq!(stx.stx_mode as libc::mode_t & libc::S_IFMT, libc::S_IFREG);
+    assert_matches(&stx, &crate::stat(&mut path_dst));
+
+    let stx = crate::statx(
+        &mut path_dst,
+        libc::AT_SYMLINK_NOFOLLOW,
+        libc::STATX_BASIC_STATS,
+    );
+    assert_eq!(stx.stx_mode as libc::mode_t & libc::S_IFMT, libc::S_IFLNK);
+    assert_matches(&stx, &crate::lstat(&mut path_dst));
+}
+
+/// statx_05: STATX_BTIME is filled in with a time no later than mtime
+#[test]
+fn statx_05() {
+    let mut path = crate::test_dir();
+    path.push("statx_05.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx =
+        crate::statx(&mut path, 0, libc::STATX_BASIC_STATS | libc::STATX_BTIME);
+    require(&stx, libc::STATX_BTIME, "STATX_BTIME");
+
+    let btime = (stx.stx_btime.tv_sec, stx.stx_btime.tv_nsec);
+    let mtime = (stx.stx_mtime.tv_sec, stx.stx_mtime.tv_nsec);
+    assert!(btime.0 > 0);
+    assert!(btime <= mtime);
+}
+
+/// statx_06: STATX_MNT_ID matches the mount id of an open fd
+#[test]
+fn statx_06() {
+    let mut path = crate::test_dir();
+    path.push("statx_06.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_MNT_ID);
+    require(&stx, libc::STATX_MNT_ID, "STATX_MNT_ID");
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd > 0);
+
+    let fdinfo = std::fs::read_to_string(format!("/proc/self/fdinfo/{fd}"))
+        .expect("Error reading fdinfo");
+    let mnt_id = fdinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("mnt_id:"))
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .expect("No mnt_id in fdinfo");
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    assert_eq!(stx.stx_mnt_id, mnt_id);
+}
+
+/// statx_07: STATX_DIOALIGN reports zero or power of two alignments
+#[test]
+fn statx_07() {
+    let mut path = crate::test_dir();
+    path.push("statx_07.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_DIOALIGN);
+    require(&stx, libc::STATX_DIOALIGN, "STATX_DIOALIGN");
+
+    // Both are zero if the file doesn't support direct I/O.
+    let mem = stx.stx_dio_mem_align;
+    let offset = stx.stx_dio_offset_align;
+    assert_eq!(mem == 0, offset == 0);
+    assert!(mem == 0 || mem.is_power_of_two());
+    assert!(offset == 0 || offset.is_power_of_two());
+}
+
+/// statx_08: A new file has only supported attributes and isn't immutable
+#[test]
+fn statx_08() {
+    let mut path = crate::test_dir();
+    path.push("statx_08.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    assert_eq!(stx.stx_attributes & !stx.stx_attributes_mask, 0);
+
+    let flags = (libc::STATX_ATTR_IMMUTABLE | libc::STATX_ATTR_APPEND) as u64;
+    assert_eq!(stx.stx_attributes & flags, 0);
+}
+
+/// statx_09: The append only flag is reported and enforced
+#[test]
+#[ignore = "needs CAP_LINUX_IMMUTABLE, run as root with --ignored"]
+fn statx_09() {
+    let mut path = crate::test_dir();
+    path.push("statx_09.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let flags = InodeFlags::add(&mut path, FS_APPEND_FL);
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    let attr = libc::STATX_ATTR_APPEND as u64;
+    assert_ne!(stx.stx_attributes_mask & attr, 0);
+    assert_ne!(stx.stx_attributes & attr, 0);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert_eq!(fd, -1);
+    assert_eq!(crate::errno(), libc::EPERM);
+
+    let fd =
+        unsafe { libc::open(path.c_str(), libc::O_WRONLY | libc::O_APPEND) };
+    assert!(fd > 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    drop(flags);
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    assert_eq!(stx.stx_attributes & attr, 0);
+}
+
+/// statx_10: The immutable flag is reported and enforced
+#[test]
+#[ignore = "needs CAP_LINUX_IMMUTABLE, run as root with --ignored"]
+fn statx_10() {
+    let mut path = crate::test_dir();
+    path.push("statx_10.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let flags = InodeFlags::add(&mut path, FS_IMMUTABLE_FL);
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    let attr = libc::STATX_ATTR_IMMUTABLE as u64;
+    assert_ne!(stx.stx_attributes_mask & attr, 0);
+    assert_ne!(stx.stx_attributes & attr, 0);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert_eq!(fd, -1);
+    assert_eq!(crate::errno(), libc::EPERM);
+
+    let err = unsafe { libc::unlink(path.c_str()) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EPERM);
+
+    drop(flags);
+
+    let stx = crate::statx(&mut path, 0, libc::STATX_BASIC_STATS);
+    assert_eq!(stx.stx_attributes & attr, 0);
+}
+
+// Restores the inode flags of a file when dropped, so that the test directory
+// can be removed even if an assertion fails.
+struct InodeFlags {
+    fd: i32,
+    old: libc::c_int,
+}
+
+impl InodeFlags {
+    // Add `flags` to the inode flags of `path`, marking the test unsupported
+    // if the filesystem doesn't have them. Setting the append only and
+    // immutable flags also needs CAP_LINUX_IMMUTABLE, without which this fails.
+    fn add(path: &mut TestPath, flags: libc::c_int) -> Self {
+        let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+        assert!(fd > 0);
+
+        let mut old: libc::c_int = 0;
+        let err = unsafe { libc::ioctl(fd, libc::FS_IOC_GETFLAGS, &mut old) };
+        if err != 0 {
+            let errno = crate::errno();
+            unsafe { libc::close(fd) };
+            if errno == libc::ENOTTY || errno == libc::EINVAL {
+                crate::unsupported("FS_IOC_GETFLAGS", errno);
+            }
+            crate::check_supported("FS_IOC_GETFLAGS", errno);
+        }
+        assert_eq!(err, 0);
+
+        let new = old | flags;
+        let err = unsafe { libc::ioctl(fd, libc::FS_IOC_SETFLAGS, &new) };
+        if err != 0 {
+            let errno = crate::errno();
+            unsafe { libc::close(fd) };
+            assert_ne!(
+                errno,
+                libc::EPERM,
+                "FS_IOC_SETFLAGS needs CAP_LINUX_IMMUTABLE"
+            );
+            if errno == libc::ENOTTY {
+                crate::unsupported("FS_IOC_SETFLAGS", errno);
+            }
+            crate::check_flag_supported("FS_IOC_SETFLAGS", errno);
+        }
+        assert_eq!(err, 0);
+
+        Self { fd, old }
+    }
+}
+
+impl Drop for InodeFlags {
+    fn drop(&mut self) {
+        unsafe {
+            libc::ioctl(self.fd, libc::FS_IOC_SETFLAGS, &self.old);
+            libc::close(self.fd);
+        }
+    }
+}
+
+// Mark the test unsupported if `flag` wasn't filled in.
+fn require(stx: &libc::statx, flag: u32, name: &str) {
+    if stx.stx_mask & flag == 0 {
+        crate::unsupported(name, libc::ENOTSUP);
+    }
+}
+
+fn assert_matches(stx: &libc::statx, stat: &libc::stat) {
+    let basic = libc::STATX_BASIC_STATS;
+    assert_eq!(stx.stx_mask & basic, basic);
+
+    assert_eq!(stx.stx_mode as libc::mode_t, stat.st_mode);
+    assert_eq!(stx.stx_ino, stat.st_ino);
+    assert_eq!(stx.stx_nlink as libc::nlink_t, stat.st_nlink);
+    assert_eq!(stx.stx_uid, stat.st_uid);
+    assert_eq!(stx.stx_gid, stat.st_gid);
+    assert_eq!(stx.stx_size as libc::off_t, stat.st_size);
+    assert_eq!(stx.stx_blocks as libc::blkcnt_t, stat.st_blocks);
+    assert_eq!(stx.stx_blksize as libc::blksize_t, stat.st_blksize);
+    assert_eq!(stx.stx_dev_major, libc::major(stat.st_dev));
+    assert_eq!(stx.stx_dev_minor, libc::minor(stat.st_dev));
+
+    let times = [
+        (&stx.stx_atime, stat.st_atime, stat.st_atime_nsec),
+        (&stx.stx_mtime, stat.st_mtime, stat.st_mtime_nsec),
+        (&stx.stx_ctime, stat.st_ctime, stat.st_ctime_nsec),
+    ];
+    for (stx_time, sec, nsec) in times {
+        assert_eq!(stx_time.tv_sec, sec);
+        assert_eq!(stx_time.tv_nsec as libc::c_long, nsec);
+    }
+}
@@ -0,0 +1,368 @@
+use std::time::Duration;
+
+use crate::TestPath;
+
+// Seconds and nanoseconds, which compare in time order.
+type Time = (libc::time_t, libc::c_long);
+
+// January 1st 2000, well before any file in a test was created.
+const OLD: libc::time_t = 946_684_800;
+
+/// time_01: write updates mtime and ctime
+#[test]
+fn time_01() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_01.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert!(fd > 0);
+
+    let len = unsafe {
+        libc::write(fd, "Goodbye".as_ptr() as *const libc::c_void, 7)
+    };
+    assert_eq!(len, 7);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    let after = crate::stat(&mut path);
+    assert!(mtime(&after) > mtime(&before));
+    assert!(ctime(&after) > ctime(&before));
+}
+
+/// time_02: chmod updates ctime but not mtime or atime
+#[test]
+fn time_02() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_02.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    let err = unsafe { libc::chmod(path.c_str(), libc::S_IRUSR) };
+    assert_eq!(err, 0);
+
+    let after = crate::stat(&mut path);
+    assert!(ctime(&after) > ctime(&before));
+    assert_eq!(mtime(&after), mtime(&before));
+    assert_eq!(atime(&after), atime(&before));
+}
+
+/// time_03: read updates an atime older than mtime unless mounted noatime
+#[cfg(target_os = "linux")]
+#[test]
+fn time_03() {
+    let mut path = crate::test_dir();
+    path.push("time_03.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set_times(&mut path, ts(OLD, 0), ts(0, libc::UTIME_OMIT));
+
+    read_all(&mut path);
+
+    let after = crate::stat(&mut path);
+    if mount_flags(&mut path) & libc::ST_NOATIME != 0 {
+        assert_eq!(atime(&after), (OLD, 0));
+    } else {
+        assert!(atime(&after) >= mtime(&after));
+    }
+}
+
+/// time_04: read only updates a recent atime when mounted strictatime
+#[cfg(target_os = "linux")]
+#[test]
+fn time_04() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_04.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    // With relatime the atime is only updated if it's not newer than the
+    // mtime and ctime, or more than a day old. The first read moves it past
+    // both so that the second one only updates it with strictatime.
+    std::thread::sleep(pause);
+    read_all(&mut path);
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    read_all(&mut path);
+
+    let after = crate::stat(&mut path);
+    let flags = mount_flags(&mut path);
+    if flags & (libc::ST_NOATIME | libc::ST_RELATIME) != 0 {
+        assert_eq!(atime(&after), atime(&before));
+    } else {
+        assert!(atime(&after) > atime(&before));
+    }
+}
+
+/// time_05: utimensat stores timestamps with nanosecond precision
+#[test]
+fn time_05() {
+    let mut path = crate::test_dir();
+    path.push("time_05.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set_times(
+        &mut path,
+        ts(1_000_000_000, 123_456_789),
+        ts(1_000_000_000, 987_654_321),
+    );
+
+    let stat = crate::stat(&mut path);
+    assert_eq!(atime(&stat), (1_000_000_000, 123_456_789));
+    assert_eq!(mtime(&stat), (1_000_000_000, 987_654_321));
+}
+
+/// time_06: UTIME_NOW sets the current time and UTIME_OMIT keeps the old one
+#[test]
+fn time_06() {
+    let mut path = crate::test_dir();
+    path.push("time_06.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set_times(&mut path, ts(OLD, 0), ts(OLD, 0));
+
+    let start = now();
+    set_times(&mut path, ts(0, libc::UTIME_NOW), ts(0, libc::UTIME_OMIT));
+    let end = now();
+
+    // File times come from a coarse clock that can lag the realtime clock.
+    let stat = crate::stat(&mut path);
+    assert!(atime(&stat).0 >= start.0 - 1);
+    assert!(atime(&stat) <= end);
+    assert_eq!(mtime(&stat), (OLD, 0));
+
+    let start = now();
+    set_times(&mut path, ts(OLD, 0), ts(0, libc::UTIME_NOW));
+    let end = now();
+
+    let stat = crate::stat(&mut path);
+    assert_eq!(atime(&stat), (OLD, 0));
+    assert!(mtime(&stat).0 >= start.0 - 1);
+    assert!(mtime(&stat) <= end);
+}
+
+/// time_07: utimensat with UTIME_OMIT for both times changes nothing
+#[test]
+fn time_07() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_07.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    set_times(&mut path, ts(0, libc::UTIME_OMIT), ts(0, libc::UTIME_OMIT));
+
+    let after = crate::stat(&mut path);
+    assert_eq!(atime(&after), atime(&before));
+    assert_eq!(mtime(&after), mtime(&before));
+    assert_eq!(ctime(&after), ctime(&before));
+}
+
+/// time_08: Setting atime and mtime updates ctime to the current time
+#[test]
+fn time_08() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_08.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    set_times(&mut path, ts(OLD, 0), ts(OLD, 0));
+
+    let after = crate::stat(&mut path);
+    assert_eq!(mtime(&after), (OLD, 0));
+    assert!(ctime(&after) > ctime(&before));
+}
+
+/// time_09: ftruncate updates mtime and ctime
+#[test]
+fn time_09() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_09.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = crate::stat(&mut path);
+    std::thread::sleep(pause);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert!(fd > 0);
+
+    let err = unsafe { libc::ftruncate(fd, 5) };
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    let after = crate::stat(&mut path);
+    assert!(mtime(&after) > mtime(&before));
+    assert!(ctime(&after) > ctime(&before));
+}
+
+/// time_10: Creating and removing entries updates the directory mtime and ctime
+#[test]
+fn time_10() {
+    let mut dir = crate::test_dir();
+    let pause = pause(&dir);
+    dir.push("time_10");
+
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    let mut path = dir.clone();
+    path.push("time_10.txt");
+
+    let before = crate::stat(&mut dir);
+    std::thread::sleep(pause);
+
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let created = crate::stat(&mut dir);
+    assert!(mtime(&created) > mtime(&before));
+    assert!(ctime(&created) > ctime(&before));
+
+    std::thread::sleep(pause);
+
+    let err = unsafe { libc::unlink(path.c_str()) };
+    assert_eq!(err, 0);
+
+    let removed = crate::stat(&mut dir);
+    assert!(mtime(&removed) > mtime(&created));
+    assert!(ctime(&removed) > ctime(&created));
+}
+
+/// time_11: statx reports a birth time that writes and utimensat don't change
+#[cfg(target_os = "linux")]
+#[test]
+fn time_11() {
+    let mut path = crate::test_dir();
+    let pause = pause(&path);
+    path.push("time_11.txt");
+
+    let start = now();
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let before = btime(&mut path);
+    assert!(before.0 >= start.0 - 1);
+    assert!(before <= mtime(&crate::stat(&mut path)));
+
+    std::thread::sleep(pause);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_WRONLY) };
+    assert!(fd > 0);
+
+    let len = unsafe {
+        libc::write(fd, "Goodbye".as_ptr() as *const libc::c_void, 7)
+    };
+    assert_eq!(len, 7);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    set_times(&mut path, ts(OLD, 0), ts(OLD, 0));
+
+    assert_eq!(btime(&mut path), before);
+}
+
+fn atime(stat: &libc::stat) -> Time {
+    (stat.st_atime, stat.st_atime_nsec)
+}
+
+fn mtime(stat: &libc::stat) -> Time {
+    (stat.st_mtime, stat.st_mtime_nsec)
+}
+
+fn ctime(stat: &libc::stat) -> Time {
+    (stat.st_ctime, stat.st_ctime_nsec)
+}
+
+// Birth time from statx, marking the test unsupported if it isn't reported.
+#[cfg(target_os = "linux")]
+fn btime(path: &mut TestPath) -> Time {
+    let stx = crate::statx(path, 0, libc::STATX_BTIME);
+    if stx.stx_mask & libc::STATX_BTIME == 0 {
+        crate::unsupported("statx birth time", libc::ENOTSUP);
+    }
+
+    (stx.stx_btime.tv_sec, stx.stx_btime.tv_nsec as libc::c_long)
+}
+
+fn now() -> Time {
+    let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+    let err = unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut now) };
+    assert_eq!(err, 0);
+    (now.tv_sec, now.tv_nsec)
+}
+
+fn ts(sec: libc::time_t, nsec: libc::c_long) -> libc::timespec {
+    let mut ts: libc::timespec = unsafe { std::mem::zeroed() };
+    ts.tv_sec = sec;
+    ts.tv_nsec = nsec;
+    ts
+}
+
+fn set_times(
+    path: &mut TestPath,
+    atime: libc::timespec,
+    mtime: libc::timespec,
+) {
+    let times = [atime, mtime];
+    let err = unsafe {
+        libc::utimensat(libc::AT_FDCWD, path.c_str(), times.as_ptr(), 0)
+    };
+    if err != 0 {
+        crate::check_supported("utimensat", crate::errno());
+    }
+    assert_eq!(err, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn read_all(path: &mut TestPath) {
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd > 0);
+
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len())
+    };
+    assert!(len > 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn mount_flags(path: &mut TestPath) -> libc::c_ulong {
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    let err = unsafe { libc::statvfs(path.c_str(), &mut stats) };
+    assert_eq!(err, 0);
+    stats.f_flag
+}
+
+// How long to wait for a new timestamp to differ from an earlier one. This
+// sets times on a scratch file in `dir`, so call it before taking any
+// timestamps of `dir` itself.
+fn pause(dir: &TestPath) -> Duration {
+    let mut path = dir.clone();
+    path.push("granularity");
+    crate::create_file_rw(&mut path, &[]);
+
+    // The clock file times are taken from only advances every few ms.
+    let nanos =
+        crate::timestamp_granularity(&mut path).unwrap_or(1_000_000_000);
+    Duration::from_nanos(nanos).max(Duration::from_millis(20))
+}
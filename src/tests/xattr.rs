@@ -0,0 +1,415 @@
+use std::ffi::CStr;
+
+use crate::TestPath;
+
+// Largest xattr value Linux accepts, from linux/limits.h.
+const XATTR_SIZE_MAX: usize = 65536;
+
+// A large value that still fits in ext4, which stores the value of an xattr
+// in a single block.
+const LARGE_VALUE: usize = 4000;
+
+/// xattr_01: Set and get a user xattr on a file
+#[test]
+fn xattr_01() {
+    let mut path = crate::test_dir();
+    path.push("xattr_01.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set(&mut path, c"user.fstesting", b"value");
+
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    assert_eq!(len, 5);
+    assert_eq!(&buf[..5], b"value");
+}
+
+/// xattr_02: Getting a missing xattr fails with ENODATA
+#[test]
+fn xattr_02() {
+    let mut path = crate::test_dir();
+    path.push("xattr_02.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    assert_eq!(len, -1);
+    crate::check_supported("getxattr", crate::errno());
+    assert_eq!(crate::errno(), libc::ENODATA);
+}
+
+/// xattr_03: XATTR_CREATE fails with EEXIST if the xattr exists
+#[test]
+fn xattr_03() {
+    let mut path = crate::test_dir();
+    path.push("xattr_03.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            b"first".as_ptr() as *const libc::c_void,
+            5,
+            libc::XATTR_CREATE,
+        )
+    };
+    if err != 0 {
+        crate::check_supported("setxattr", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            b"second".as_ptr() as *const libc::c_void,
+            6,
+            libc::XATTR_CREATE,
+        )
+    };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::EEXIST);
+
+    assert_eq!(get(&mut path, c"user.fstesting"), b"first");
+}
+
+/// xattr_04: XATTR_REPLACE fails with ENODATA unless the xattr exists
+#[test]
+fn xattr_04() {
+    let mut path = crate::test_dir();
+    path.push("xattr_04.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            b"first".as_ptr() as *const libc::c_void,
+            5,
+            libc::XATTR_REPLACE,
+        )
+    };
+    assert_eq!(err, -1);
+    crate::check_supported("setxattr", crate::errno());
+    assert_eq!(crate::errno(), libc::ENODATA);
+
+    set(&mut path, c"user.fstesting", b"first");
+
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            b"second".as_ptr() as *const libc::c_void,
+            6,
+            libc::XATTR_REPLACE,
+        )
+    };
+    assert_eq!(err, 0);
+
+    assert_eq!(get(&mut path, c"user.fstesting"), b"second");
+}
+
+/// xattr_05: Probe the size of a value with a zero length buffer
+#[test]
+fn xattr_05() {
+    let mut path = crate::test_dir();
+    path.push("xattr_05.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set(&mut path, c"user.fstesting", b"Hello, World!");
+
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    assert_eq!(len, 13);
+}
+
+/// xattr_06: Getting a value into a short buffer fails with ERANGE
+#[test]
+fn xattr_06() {
+    let mut path = crate::test_dir();
+    path.push("xattr_06.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set(&mut path, c"user.fstesting", b"Hello, World!");
+
+    let mut buf = vec![0u8; 12];
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    assert_eq!(len, -1);
+    assert_eq!(crate::errno(), libc::ERANGE);
+}
+
+/// xattr_07: List xattr names, probing the size and checking ERANGE
+#[test]
+fn xattr_07() {
+    let mut path = crate::test_dir();
+    path.push("xattr_07.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set(&mut path, c"user.first", b"1");
+    set(&mut path, c"user.second", b"2");
+
+    let len = unsafe { libc::listxattr(path.c_str(), std::ptr::null_mut(), 0) };
+    assert!(len > 0);
+
+    let mut buf = vec![0u8; len as usize];
+    let len = unsafe {
+        libc::listxattr(
+            path.c_str(),
+            buf.as_mut_ptr() as *mut libc::c_char,
+            buf.len(),
+        )
+    };
+    assert_eq!(len as usize, buf.len());
+
+    // Other namespaces, like security.*, may be listed as well.
+    let names = buf
+        .split(|c| *c == 0)
+        .filter(|name| name.starts_with(b"user."))
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&&b"user.first"[..]));
+    assert!(names.contains(&&b"user.second"[..]));
+
+    let mut buf = vec![0u8; buf.len() - 1];
+    let len = unsafe {
+        libc::listxattr(
+            path.c_str(),
+            buf.as_mut_ptr() as *mut libc::c_char,
+            buf.len(),
+        )
+    };
+    assert_eq!(len, -1);
+    assert_eq!(crate::errno(), libc::ERANGE);
+}
+
+/// xattr_08: Remove an xattr
+#[test]
+fn xattr_08() {
+    let mut path = crate::test_dir();
+    path.push("xattr_08.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    set(&mut path, c"user.fstesting", b"value");
+
+    let err =
+        unsafe { libc::removexattr(path.c_str(), c"user.fstesting".as_ptr()) };
+    assert_eq!(err, 0);
+
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    assert_eq!(len, -1);
+    assert_eq!(crate::errno(), libc::ENODATA);
+
+    let err =
+        unsafe { libc::removexattr(path.c_str(), c"user.fstesting".as_ptr()) };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::ENODATA);
+}
+
+/// xattr_09: Set and get a large value
+#[test]
+fn xattr_09() {
+    let mut path = crate::test_dir();
+    path.push("xattr_09.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let value = (0..LARGE_VALUE)
+        .map(|i| 97 + (i % 26) as u8)
+        .collect::<Vec<_>>();
+    set(&mut path, c"user.fstesting", &value);
+
+    assert_eq!(get(&mut path, c"user.fstesting"), value);
+}
+
+/// xattr_10: Values larger than XATTR_SIZE_MAX fail with E2BIG
+#[test]
+fn xattr_10() {
+    let mut path = crate::test_dir();
+    path.push("xattr_10.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let value = vec![b'a'; XATTR_SIZE_MAX + 1];
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            c"user.fstesting".as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+        )
+    };
+    assert_eq!(err, -1);
+    assert_eq!(crate::errno(), libc::E2BIG);
+}
+
+/// xattr_11: Set and get an xattr on a directory
+#[test]
+fn xattr_11() {
+    let mut path = crate::test_dir();
+    path.push("xattr_11");
+
+    let err = unsafe { libc::mkdir(path.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    set(&mut path, c"user.fstesting", b"value");
+    assert_eq!(get(&mut path, c"user.fstesting"), b"value");
+}
+
+/// xattr_12: User xattrs are set through a symlink, not on it
+#[test]
+fn xattr_12() {
+    let mut path_src = crate::test_dir();
+    let mut path_dst = path_src.clone();
+
+    path_src.push("xattr_12_src.txt");
+    path_dst.push("xattr_12_dst.txt");
+
+    crate::create_file_rw(&mut path_src, "Hello, World!".as_bytes());
+
+    let err = unsafe {
+        libc::symlink(c"xattr_12_src.txt".as_ptr(), path_dst.c_str())
+    };
+    assert_eq!(err, 0);
+
+    // Linux only allows user xattrs on regular files and directories.
+    let err = unsafe {
+        libc::lsetxattr(
+            path_dst.c_str(),
+            c"user.fstesting".as_ptr(),
+            b"value".as_ptr() as *const libc::c_void,
+            5,
+            0,
+        )
+    };
+    assert_eq!(err, -1);
+    crate::check_supported("lsetxattr", crate::errno());
+    assert_eq!(crate::errno(), libc::EPERM);
+
+    set(&mut path_dst, c"user.fstesting", b"value");
+    assert_eq!(get(&mut path_src, c"user.fstesting"), b"value");
+
+    let len = unsafe {
+        libc::lgetxattr(
+            path_dst.c_str(),
+            c"user.fstesting".as_ptr(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    assert_eq!(len, -1);
+    assert_eq!(crate::errno(), libc::ENODATA);
+}
+
+/// xattr_13: Xattrs set through a fd persist after close and reopen
+#[test]
+fn xattr_13() {
+    let mut path = crate::test_dir();
+    path.push("xattr_13.txt");
+    crate::create_file_rw(&mut path, "Hello, World!".as_bytes());
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDWR) };
+    assert!(fd > 0);
+
+    let err = unsafe {
+        libc::fsetxattr(
+            fd,
+            c"user.fstesting".as_ptr(),
+            b"value".as_ptr() as *const libc::c_void,
+            5,
+            0,
+        )
+    };
+    if err != 0 {
+        crate::check_supported("fsetxattr", crate::errno());
+    }
+    assert_eq!(err, 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
+    assert!(fd > 0);
+
+    let mut buf = vec![0u8; 1024];
+    let len = unsafe {
+        libc::fgetxattr(
+            fd,
+            c"user.fstesting".as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    assert_eq!(len, 5);
+    assert_eq!(&buf[..5], b"value");
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+
+    assert_eq!(get(&mut path, c"user.fstesting"), b"value");
+}
+
+// Set an xattr, marking the test unsupported if the filesystem has no xattrs.
+fn set(path: &mut TestPath, name: &CStr, value: &[u8]) {
+    let err = unsafe {
+        libc::setxattr(
+            path.c_str(),
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+        )
+    };
+    if err != 0 {
+        crate::check_supported("setxattr", crate::errno());
+    }
+    assert_eq!(err, 0);
+}
+
+fn get(path: &mut TestPath, name: &CStr) -> Vec<u8> {
+    let mut buf = vec![0u8; XATTR_SIZE_MAX];
+    let len = unsafe {
+        libc::getxattr(
+            path.c_str(),
+            name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    assert!(len >= 0);
+    buf.truncate(len as usize);
+    buf
+}
@@ -1,4 +1,4 @@
-use crate::test_path::TestPath;
+use crate::test_path::{TestDir, TestPath};
 use crate::wrappers;
 
 pub fn create_file(path: &mut TestPath, data: &[u8]) {
@@ -27,6 +27,32 @@ pub fn create_file_impl(path: &mut TestPath, data: &[u8], perms: libc::mode_t) {
     assert_eq!(err, 0);
 }
 
+/// Create an empty file that must not exist yet, without syncing it.
+pub fn create_empty_file(path: &mut TestPath) {
+    let fd = unsafe {
+        wrappers::open3(
+            path.c_str(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+            libc::S_IRUSR | libc::S_IWUSR,
+        )
+    };
+    assert!(fd > 0);
+
+    let err = unsafe { libc::close(fd) };
+    assert_eq!(err, 0);
+}
+
+/// Create a directory called `name` in a new test directory.
+pub fn create_test_subdir(name: &str) -> TestDir {
+    let mut dir = crate::test_path::test_dir();
+    dir.push(name);
+
+    let err = unsafe { libc::mkdir(dir.c_str(), 0o700) };
+    assert_eq!(err, 0);
+
+    dir
+}
+
 pub fn read_file(path: &mut TestPath) -> String {
     let fd = unsafe { libc::open(path.c_str(), libc::O_RDONLY) };
     assert!(fd > 0);
@@ -58,6 +84,38 @@ pub fn errno() -> i32 {
     std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
 }
 
+/// Panic message prefix marking a test as unsupported rather than failed.
+pub const UNSUPPORTED: &str = "fstesting: unsupported";
+
+/// Stop the current test because the filesystem does not support `op`.
+///
+/// The test is still reported as failed by libtest, but the report tools
+/// recognize the panic message and show it as unsupported.
+pub fn unsupported(op: &str, errno: i32) -> ! {
+    let err = std::io::Error::from_raw_os_error(errno);
+    panic!("{UNSUPPORTED}: {op}: {err}");
+}
+
+/// Mark the test unsupported if `errno` says `op` is not implemented.
+pub fn check_supported(op: &str, errno: i32) {
+    if errno == libc::ENOSYS
+        || errno == libc::EOPNOTSUPP
+        || errno == libc::ENOTSUP
+    {
+        unsupported(op, errno);
+    }
+}
+
+/// Like `check_supported` but also treats `EINVAL` as unsupported, for calls
+/// where that is how an unknown flag or mode is reported.
+pub fn check_flag_supported(op: &str, errno: i32) {
+    if errno == libc::EINVAL {
+        unsupported(op, errno);
+    }
+
+    check_supported(op, errno);
+}
+
 pub fn perror(msg: &str) {
     unsafe { libc::perror(msg.as_ptr() as *const libc::c_char) }
     eprintln!();
@@ -85,6 +143,22 @@ pub fn lstat(path: &mut TestPath) -> libc::stat {
     }
 }
 
+/// `statx` relative to the current directory, marking the test unsupported
+/// if the kernel or filesystem doesn't implement it.
+#[cfg(target_os = "linux")]
+pub fn statx(path: &mut TestPath, flags: i32, mask: u32) -> libc::statx {
+    unsafe {
+        let mut stx: libc::statx = std::mem::zeroed();
+        let err =
+            libc::statx(libc::AT_FDCWD, path.c_str(), flags, mask, &mut stx);
+        if err != 0 {
+            check_supported("statx", errno());
+        }
+        assert_eq!(err, 0);
+        stx
+    }
+}
+
 pub fn statfs(path: &mut TestPath) -> libc::statfs {
     unsafe {
         let mut stats: libc::statfs = std::mem::zeroed();
@@ -93,3 +167,46 @@ pub fn statfs(path: &mut TestPath) -> libc::statfs {
         stats
     }
 }
+
+/// Detect the granularity of the timestamps stored for `path`, in nanoseconds.
+///
+/// This sets the modification time of `path` and returns None if that fails.
+/// The time is an odd second with the largest nanosecond value, so the stored
+/// time is a multiple of the granularity and of nothing coarser. That is the
+/// largest power of ten dividing it, or two seconds for FAT style timestamps.
+pub fn timestamp_granularity(path: &mut TestPath) -> Option<u64> {
+    const SEC: u64 = 1_000_000_001;
+    const NSEC: u64 = 999_999_999;
+
+    unsafe {
+        let mut times: [libc::timespec; 2] = std::mem::zeroed();
+        times[0].tv_nsec = libc::UTIME_OMIT;
+        times[1].tv_sec = SEC as libc::time_t;
+        times[1].tv_nsec = NSEC as libc::c_long;
+
+        let err =
+            libc::utimensat(libc::AT_FDCWD, path.c_str(), times.as_ptr(), 0);
+        if err != 0 {
+            return None;
+        }
+
+        let mut stat: libc::stat = std::mem::zeroed();
+        if libc::stat(path.c_str(), &mut stat) != 0 {
+            return None;
+        }
+
+        let got =
+            stat.st_mtime as u64 * 1_000_000_000 + stat.st_mtime_nsec as u64;
+        if got.is_multiple_of(2_000_000_000) {
+            return Some(2_000_000_000);
+        }
+
+        let mut granularity = 1;
+        while granularity < 1_000_000_000
+            && got.is_multiple_of(granularity * 10)
+        {
+            granularity *= 10;
+        }
+        Some(granularity)
+    }
+}
@@ -7,3 +7,5 @@ int open3(const char *path, int oflags, mode_t mode) {
 }
 
 int fcntl_int(int fd, int cmd, int arg) { return fcntl(fd, cmd, arg); }
+
+int fcntl_ptr(int fd, int cmd, void *arg) { return fcntl(fd, cmd, arg); }
@@ -1,6 +1,8 @@
 unsafe extern "C" {
     pub fn fcntl_int(fd: i32, cmd: i32, arg: i32) -> i32;
 
+    pub fn fcntl_ptr(fd: i32, cmd: i32, arg: *mut libc::c_void) -> i32;
+
     pub fn open3(
         path: *const libc::c_char,
         oflag: libc::c_int,